@@ -0,0 +1,103 @@
+use crate::map::Map;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+// Reference: http://weblog.jamisbuck.org/2011/1/3/maze-generation-braid-mazes
+
+/// Returns the indices of all of the dead ends in `map`, i.e. the cells that
+/// only have a single open passage.
+pub fn get_dead_ends(map: &Map) -> Vec<(usize, usize)> {
+    map.get_all_grid_indices()
+        .into_iter()
+        .filter(|(i, j)| map.get_open_neighbors(*i, *j).len() == 1)
+        .collect()
+}
+
+/// Removes dead ends from `map` by opening a wall from each dead end to one of
+/// its neighbors, which introduces loops into the maze. Each dead end is removed
+/// with the specified `probability`, so a value of 1.0 produces a fully braided
+/// maze (without any dead ends) and smaller values produce partially braided
+/// mazes. Neighbors that are dead ends themselves are preferred, since opening
+/// a wall between two dead ends removes both of them at once.
+///
+/// Probabilities outside of the range 0.0 to 1.0 are clamped to it, but the
+/// probability must be a number.
+pub fn braid(map: &mut Map, probability: f32, rng: &mut dyn RngCore) {
+    assert!(
+        !probability.is_nan(),
+        "Attempting to braid with a probability that isn't a number"
+    );
+    let probability = probability.clamp(0.0, 1.0) as f64;
+
+    let mut dead_ends = get_dead_ends(map);
+    dead_ends.shuffle(rng);
+
+    for current in dead_ends {
+        // An earlier step may have already removed this dead end
+        if map.get_open_neighbors(current.0, current.1).len() != 1 || !rng.gen_bool(probability) {
+            continue;
+        }
+
+        let closed = map.get_closed_neighbors(current.0, current.1);
+
+        let preferred: Vec<(usize, usize)> = closed
+            .iter()
+            .cloned()
+            .filter(|(i, j)| map.get_open_neighbors(*i, *j).len() == 1)
+            .collect();
+
+        let candidates = if preferred.is_empty() {
+            closed
+        } else {
+            preferred
+        };
+
+        if let Some(next) = candidates.choose(rng) {
+            map.open_path_between(current, *next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::MapBuilder;
+
+    #[test]
+    fn test_braid() {
+        let map = MapBuilder::new((12, 12))
+            .post_process(|map, rng| braid(map, 1.0, rng))
+            .build();
+
+        assert!(get_dead_ends(&map).is_empty());
+    }
+
+    #[test]
+    fn test_braid_none() {
+        let mut map = Map::from_seed((12, 12), 5);
+        let before = get_dead_ends(&map);
+        braid(&mut map, 0.0, &mut rand::thread_rng());
+
+        assert!(!before.is_empty());
+        assert_eq!(get_dead_ends(&map), before);
+    }
+
+    #[test]
+    fn test_braid_clamped() {
+        let mut map = Map::from_seed((8, 8), 5);
+        braid(&mut map, 2.5, &mut rand::thread_rng());
+        assert!(get_dead_ends(&map).is_empty());
+
+        let mut map = Map::from_seed((8, 8), 5);
+        let before = get_dead_ends(&map);
+        braid(&mut map, -1.0, &mut rand::thread_rng());
+        assert_eq!(get_dead_ends(&map), before);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_braid_nan() {
+        let mut map = Map::from_seed((4, 4), 5);
+        braid(&mut map, f32::NAN, &mut rand::thread_rng());
+    }
+}
@@ -0,0 +1,225 @@
+use crate::generators::{Generator, Prims};
+use crate::map::Map;
+use crate::mask::Mask;
+use crate::topology::Topology;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// A post-processing step that is applied to a map after its maze has been generated.
+pub type PostProcess = Box<dyn Fn(&mut Map, &mut dyn RngCore)>;
+
+/// A builder for configuring and populating a new map, i.e. choosing which
+/// generator to use, how to seed it, and what to do with the maze afterwards.
+pub struct MapBuilder {
+    // The dimensions of the map (rows, columns)
+    dimensions: (usize, usize),
+
+    // The shape of the cells and how they are connected to one another
+    topology: Topology,
+
+    // The algorithm used to generate the maze
+    generator: Box<dyn Generator>,
+
+    // The seed for the random number generator (if `None`, the maze will be
+    // different every time)
+    seed: Option<u64>,
+
+    // The cell that the generator should start carving from (if `None`, a
+    // random cell is chosen instead)
+    start: Option<(usize, usize)>,
+
+    // The shape that the maze is restricted to (if `None`, the maze fills the
+    // entire map)
+    mask: Option<Mask>,
+
+    // Whether or not passages are allowed to tunnel underneath other passages
+    weave: bool,
+
+    // Steps that are applied, in order, after the maze has been generated
+    post_processing: Vec<PostProcess>,
+
+    // Whether or not to open an entrance and an exit at either end of the longest
+    // path through the maze
+    entrance_and_exit: bool,
+}
+
+impl MapBuilder {
+    /// Constructs a new builder for a map with the specified `dimensions`, which
+    /// will be populated using Prim's algorithm unless another generator is set.
+    pub fn new(dimensions: (usize, usize)) -> MapBuilder {
+        MapBuilder {
+            dimensions,
+            topology: Topology::Square,
+            generator: Box::new(Prims {}),
+            seed: None,
+            start: None,
+            mask: None,
+            weave: false,
+            post_processing: vec![],
+            entrance_and_exit: false,
+        }
+    }
+
+    /// Sets the topology of the map, i.e. the shape of its cells.
+    pub fn topology(mut self, topology: Topology) -> MapBuilder {
+        self.topology = topology;
+        self
+    }
+
+    /// Sets the algorithm used to generate the maze.
+    pub fn generator(mut self, generator: impl Generator + 'static) -> MapBuilder {
+        self.generator = Box::new(generator);
+        self
+    }
+
+    /// Sets the seed of the random number generator, so that the same seed (and
+    /// configuration) always produces the same maze.
+    pub fn seed(mut self, seed: u64) -> MapBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the cell that the generator should start carving from.
+    pub fn start(mut self, start: (usize, usize)) -> MapBuilder {
+        self.start = Some(start);
+        self
+    }
+
+    /// Restricts the maze to the shape described by `mask`, which must have the
+    /// same dimensions as the map.
+    pub fn mask(mut self, mask: Mask) -> MapBuilder {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Sets whether or not passages are allowed to tunnel underneath other passages
+    /// (see `Map::set_weave`).
+    pub fn weave(mut self, weave: bool) -> MapBuilder {
+        self.weave = weave;
+        self
+    }
+
+    /// Adds a step that will be applied to the map after its maze has been
+    /// generated. Steps are applied in the order that they are added and share
+    /// the builder's random number generator.
+    pub fn post_process(
+        mut self,
+        step: impl Fn(&mut Map, &mut dyn RngCore) + 'static,
+    ) -> MapBuilder {
+        self.post_processing.push(Box::new(step));
+        self
+    }
+
+    /// Sets whether or not to place an entrance and an exit along the edge of the
+    /// map, as far apart as possible, once every other step has been applied (see
+    /// `Map::place_entrance_and_exit`).
+    pub fn entrance_and_exit(mut self, entrance_and_exit: bool) -> MapBuilder {
+        self.entrance_and_exit = entrance_and_exit;
+        self
+    }
+
+    /// Constructs and populates the map.
+    pub fn build(self) -> Map {
+        let mut rng: Box<dyn RngCore> = match self.seed {
+            Some(seed) => Box::new(ChaCha8Rng::seed_from_u64(seed)),
+            None => Box::new(rand::thread_rng()),
+        };
+
+        let mut map = Map::with_topology(self.dimensions, self.topology);
+        map.set_mask(self.mask);
+        map.set_weave(self.weave);
+        map.set_start(self.start);
+        map.build_maze(self.generator.as_ref(), rng.as_mut());
+
+        for step in self.post_processing.iter() {
+            step(&mut map, rng.as_mut());
+        }
+
+        if self.entrance_and_exit {
+            map.place_entrance_and_exit();
+        }
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::Backtracking;
+
+    #[test]
+    fn test_seed() {
+        let a = MapBuilder::new((10, 12)).seed(42).build();
+        let b = Map::from_seed((10, 12), 42);
+
+        assert_eq!(format!("{:?}", a), format!("{:?}", b));
+    }
+
+    #[test]
+    fn test_start() {
+        let map = MapBuilder::new((6, 6))
+            .generator(Backtracking {})
+            .start((5, 2))
+            .build();
+
+        assert_eq!(map.get_start(), Some((5, 2)));
+        assert!(map.get_terrain().iter().all(|cell| cell.visited));
+    }
+
+    #[test]
+    fn test_post_process() {
+        let map = MapBuilder::new((5, 5))
+            .post_process(|map, _| map.open_all_paths())
+            .build();
+
+        assert!(map.get_terrain().iter().enumerate().all(|(idx, cell)| {
+            let (i, j) = map.absolute_to_grid_indices(idx);
+            map.get_open_neighbors(i, j).len() == map.get_neighbors(i, j).len() && cell.visited
+        }));
+    }
+
+    #[test]
+    fn test_topology() {
+        let map = MapBuilder::new((5, 7)).topology(Topology::Hex).build();
+
+        assert_eq!(map.get_topology(), Topology::Hex);
+        assert!(map.get_terrain().iter().all(|cell| cell.visited));
+
+        // A single column of triangles falls apart into pieces, but isn't masked
+        let map = MapBuilder::new((3, 1)).topology(Topology::Triangle).build();
+        assert_eq!(map.get_mask(), None);
+    }
+
+    #[test]
+    fn test_mask() {
+        let mask = Mask::from_fn((5, 5), |i, j| i == 2 || j == 2);
+        let map = MapBuilder::new((5, 5)).mask(mask).build();
+
+        // Only the cells of the cross are carved, and the corners are left untouched
+        assert_eq!(map.get_cell_count(), 9);
+        assert!(map.get_cell(2, 0).visited);
+        assert!(!map.get_cell(0, 0).visited);
+    }
+
+    #[test]
+    fn test_entrance_and_exit() {
+        let map = MapBuilder::new((6, 8)).seed(7).build();
+        assert_eq!(map.get_entrance(), None);
+
+        let mut map = MapBuilder::new((6, 8))
+            .seed(7)
+            .entrance_and_exit(true)
+            .build();
+        let (entrance, exit) = (map.get_entrance().unwrap(), map.get_exit().unwrap());
+
+        // The openings match the ones that would be placed by hand
+        map.set_entrance(None);
+        map.set_exit(None);
+        assert_eq!(
+            map.place_entrance_and_exit()
+                .map(|path| (path[0], path[path.len() - 1])),
+            Some((entrance, exit))
+        );
+    }
+}
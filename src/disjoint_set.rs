@@ -0,0 +1,89 @@
+// Reference: https://en.wikipedia.org/wiki/Disjoint-set_data_structure
+
+/// A disjoint-set (union-find) structure over the elements `0..len`, using
+/// union by rank and path compression.
+pub struct DisjointSet {
+    // The parent of each element (an element is a root if it is its own parent)
+    parents: Vec<usize>,
+
+    // An upper bound on the height of the tree rooted at each element
+    ranks: Vec<usize>,
+}
+
+impl DisjointSet {
+    /// Constructs a new disjoint-set where each of the `len` elements starts out
+    /// in its own set.
+    pub fn new(len: usize) -> DisjointSet {
+        DisjointSet {
+            parents: (0..len).collect(),
+            ranks: vec![0; len],
+        }
+    }
+
+    /// Returns the representative (root) of the set containing `element`.
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        // Point every element along the way directly at the root
+        let mut current = element;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+
+        root
+    }
+
+    /// Returns `true` if `a` and `b` belong to the same set.
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns `false` if they were
+    /// already in the same set, and `true` otherwise.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let root_a = self.find(a);
+        let root_b = self.find(b);
+
+        if root_a == root_b {
+            return false;
+        }
+
+        // Attach the shorter tree below the taller one
+        if self.ranks[root_a] < self.ranks[root_b] {
+            self.parents[root_a] = root_b;
+        } else if self.ranks[root_a] > self.ranks[root_b] {
+            self.parents[root_b] = root_a;
+        } else {
+            self.parents[root_b] = root_a;
+            self.ranks[root_a] += 1;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut set = DisjointSet::new(5);
+
+        assert!(!set.connected(0, 1));
+        assert!(set.union(0, 1));
+        assert!(set.union(3, 4));
+        assert!(set.connected(0, 1));
+        assert!(!set.connected(1, 3));
+
+        assert!(set.union(1, 4));
+        assert!(set.connected(0, 3));
+        assert!(!set.union(0, 4));
+        assert!(!set.connected(2, 0));
+    }
+}
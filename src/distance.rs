@@ -0,0 +1,370 @@
+use crate::graph::Graph;
+use crate::map::Map;
+use crate::search::{explore, CameFrom, Order, SolveError};
+use crate::svg;
+use std::cmp::Reverse;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+// References:
+// http://weblog.jamisbuck.org/2011/1/4/maze-solving-dijkstra-s-algorithm
+// https://www.redblobgames.com/pathfinding/a-star/introduction.html#dijkstra
+
+/// The colors that a heatmap blends between, from the closest cells to the farthest.
+const GRADIENT: [(u8, u8, u8); 3] = [(68, 1, 84), (33, 145, 140), (253, 231, 37)];
+
+/// The distance from a single source cell to every cell that can be reached from it,
+/// along with the route that each of those cells was reached by.
+#[derive(Clone, Debug)]
+pub struct DistanceGrid<N = (usize, usize)> {
+    // The cell that all distances are measured from
+    source: N,
+
+    // For each reachable cell: the cell it was reached from, and its distance
+    distances: CameFrom<N>,
+}
+
+/// Measures the distance from `source` to every cell of `map` that can be reached
+/// from it, by flooding outwards until there is nowhere left to go. Distances are
+/// measured in the same units as `Graph::get_cost`, so on a weave maze, passing
+/// through a tunnel counts for more than an ordinary step. Like the solvers, this
+/// reports a source that isn't part of the maze as an error rather than panicking.
+pub fn distances<G: Graph>(
+    map: &G,
+    source: G::Node,
+) -> Result<DistanceGrid<G::Node>, SolveError<G::Node>> {
+    if !map.contains(source) {
+        return Err(SolveError::OutOfBounds(source));
+    }
+
+    let (distances, _) = explore(map, source, None, Order::Cheapest);
+    Ok(DistanceGrid { source, distances })
+}
+
+/// Returns the longest of all the shortest paths through `map`, i.e. the path
+/// between the two cells that are farthest apart, from one end to the other. Its
+/// length is known as the diameter of the maze.
+pub fn diameter<G: Graph>(map: &G) -> Vec<G::Node> {
+    // Every node is part of the maze, so this can't fail
+    longest_path_between(map, &map.get_nodes()).unwrap()
+}
+
+/// Returns the longest shortest path between any two of the cells in `nodes`, from
+/// one end to the other. Only the cells that can be reached from the first of
+/// `nodes` are considered, and if there are none, the path is empty. Any cell
+/// that isn't part of the maze is reported as an error.
+///
+/// In a perfect maze, where there is exactly one path between any two cells, the
+/// cell farthest from any cell is always at one end of a longest path, so two
+/// floods are enough to find it. Otherwise, every cell is flooded from in turn.
+pub fn longest_path_between<G: Graph>(
+    map: &G,
+    nodes: &[G::Node],
+) -> Result<Vec<G::Node>, SolveError<G::Node>> {
+    if let Some(node) = nodes.iter().find(|node| !map.contains(**node)) {
+        return Err(SolveError::OutOfBounds(*node));
+    }
+
+    let first = match nodes.first() {
+        Some(first) => distances(map, *first)?,
+        None => return Ok(vec![]),
+    };
+
+    let (grid, farthest) = if first.is_perfect(map) {
+        let (start, _) = first.get_farthest_of(nodes).unwrap();
+        let grid = distances(map, start)?;
+        let (end, _) = grid.get_farthest_of(nodes).unwrap();
+        (grid, end)
+    } else {
+        let (mut end, mut longest) = first.get_farthest_of(nodes).unwrap();
+        let mut best = first.clone();
+
+        for node in nodes.iter().filter(|node| first.get(**node).is_some()) {
+            let grid = distances(map, *node)?;
+            let (farthest, distance) = grid.get_farthest_of(nodes).unwrap();
+            if distance > longest {
+                end = farthest;
+                longest = distance;
+                best = grid;
+            }
+        }
+        (best, end)
+    };
+
+    let mut path = grid.get_path_to_source(farthest).unwrap();
+    path.reverse();
+    Ok(path)
+}
+
+impl<N: Copy + Eq + std::hash::Hash + Ord> DistanceGrid<N> {
+    /// Returns the cell that all distances are measured from.
+    pub fn get_source(&self) -> N {
+        self.source
+    }
+
+    /// Returns the distance from the source to `node`, or `None` if it can't be
+    /// reached (or isn't part of the maze at all).
+    pub fn get(&self, node: N) -> Option<usize> {
+        self.distances.get(&node).map(|(_, distance)| *distance)
+    }
+
+    /// Returns the number of cells that can be reached from the source, including
+    /// the source itself.
+    pub fn get_reachable_count(&self) -> usize {
+        self.distances.len()
+    }
+
+    /// Returns the distance to the farthest cell that can be reached from the source.
+    pub fn get_max_distance(&self) -> usize {
+        self.get_farthest().1
+    }
+
+    /// Returns the cell that is farthest from the source, along with its distance.
+    /// Ties are broken in favor of the smallest cell, so the result doesn't depend
+    /// on the order in which cells were reached.
+    pub fn get_farthest(&self) -> (N, usize) {
+        Self::get_max(self.distances.iter().map(|(node, (_, d))| (*node, *d))).unwrap()
+    }
+
+    /// Returns the cell among `nodes` that is farthest from the source, along with
+    /// its distance, or `None` if none of them can be reached. Ties are broken in
+    /// the same way as `get_farthest`.
+    pub fn get_farthest_of(&self, nodes: &[N]) -> Option<(N, usize)> {
+        Self::get_max(
+            nodes
+                .iter()
+                .filter_map(|node| Some((*node, self.get(*node)?))),
+        )
+    }
+
+    /// Returns the pair of a cell and its distance with the largest distance (or the
+    /// smallest cell, if several are equally far).
+    fn get_max(distances: impl Iterator<Item = (N, usize)>) -> Option<(N, usize)> {
+        distances.max_by_key(|(node, distance)| (*distance, Reverse(*node)))
+    }
+
+    /// Returns `true` if there is exactly one path from the source to each of the
+    /// cells that can be reached from it, i.e. if the passages between them form a
+    /// tree, as they do in a perfect maze.
+    pub fn is_perfect<G: Graph<Node = N>>(&self, map: &G) -> bool {
+        let ends: usize = self
+            .distances
+            .keys()
+            .map(|node| map.get_open_neighbors(*node).len())
+            .sum();
+
+        // Each passage has two ends, and a tree has one fewer passage than cells
+        ends == 2 * (self.distances.len() - 1)
+    }
+
+    /// Returns the shortest path from `node` back to the source, starting with
+    /// `node` and ending with the source, or `None` if `node` can't be reached.
+    pub fn get_path_to_source(&self, node: N) -> Option<Vec<N>> {
+        self.distances.get(&node)?;
+
+        let mut path = vec![node];
+        let mut current = node;
+        while current != self.source {
+            current = self.distances[&current].0;
+            path.push(current);
+        }
+
+        Some(path)
+    }
+}
+
+/// Returns the color of a cell `t` of the way from the source to the farthest cell,
+/// where `t` runs from 0 to 1.
+fn get_heat_color(t: f32) -> (u8, u8, u8) {
+    let t = t.clamp(0.0, 1.0) * (GRADIENT.len() - 1) as f32;
+    let idx = (t as usize).min(GRADIENT.len() - 2);
+    let (a, b) = (GRADIENT[idx], GRADIENT[idx + 1]);
+
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * (t - idx as f32)).round() as u8;
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+impl DistanceGrid {
+    /// Returns the heatmap color of cell <`i`, `j`>, or `None` if it can't be reached.
+    pub fn get_color(&self, i: usize, j: usize) -> Option<(u8, u8, u8)> {
+        self.get_scaled_color(i, j, self.get_max_distance())
+    }
+
+    /// Returns the heatmap color of cell <`i`, `j`>, on a scale that runs from the
+    /// source out to a distance of `max`.
+    fn get_scaled_color(&self, i: usize, j: usize, max: usize) -> Option<(u8, u8, u8)> {
+        self.get((i, j))
+            .map(|distance| get_heat_color(distance as f32 / max.max(1) as f32))
+    }
+
+    /// Renders the distances as a heatmap for a truecolor terminal, one row of the
+    /// map per line, with each cell drawn as a block of two spaces. Cells that can't
+    /// be reached are left blank.
+    pub fn to_ansi(&self, map: &Map) -> String {
+        let max = self.get_max_distance();
+        let mut ansi = String::new();
+
+        for i in 0..map.get_dimensions().0 {
+            for j in 0..map.get_row_width(i) {
+                match self.get_scaled_color(i, j, max) {
+                    Some((r, g, b)) => write!(ansi, "\x1b[48;2;{};{};{}m  ", r, g, b).unwrap(),
+                    None => ansi.push_str("\x1b[0m  "),
+                }
+            }
+            ansi.push_str("\x1b[0m\n");
+        }
+
+        ansi
+    }
+
+    /// Saves an SVG drawing of `map` to `path`, with the background of each cell
+    /// colored by its distance from the source (see `svg::render_filled`).
+    pub fn save_svg(&self, map: &Map, path: &Path, cell_size: f32) -> std::io::Result<()> {
+        let max = self.get_max_distance();
+        let svg = svg::render_filled(map, cell_size, &|(i, j)| self.get_scaled_color(i, j, max));
+
+        let mut file = File::create(path)?;
+        file.write_all(svg.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphMaze;
+    use crate::mask::Mask;
+
+    #[test]
+    fn test_open_room() {
+        let mut map = Map::empty((5, 5));
+        map.open_all_paths();
+
+        let grid = distances(&map, (0, 0)).unwrap();
+        assert_eq!(grid.get_source(), (0, 0));
+        assert_eq!(grid.get((0, 0)), Some(0));
+        assert_eq!(grid.get((2, 3)), Some(5));
+        assert_eq!(grid.get_reachable_count(), 25);
+        assert_eq!(grid.get_farthest(), ((4, 4), 8));
+        assert_eq!(grid.get_max_distance(), 8);
+
+        let path = grid.get_path_to_source((4, 4)).unwrap();
+        assert_eq!(path.len(), 9);
+        assert_eq!((path[0], path[8]), ((4, 4), (0, 0)));
+        assert_eq!(grid.get_path_to_source((0, 0)), Some(vec![(0, 0)]));
+    }
+
+    #[test]
+    fn test_unreachable() {
+        // A corridor along the top row, cut off from the rest of the map
+        let mut map = Map::empty((2, 3));
+        map.open_path_between((0, 0), (0, 1));
+        map.open_path_between((0, 1), (0, 2));
+
+        let grid = distances(&map, (0, 2)).unwrap();
+        assert_eq!(grid.get_farthest(), ((0, 0), 2));
+        assert_eq!(grid.get((1, 0)), None);
+        assert_eq!(grid.get_path_to_source((1, 0)), None);
+        assert_eq!(grid.get_color(1, 0), None);
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = GraphMaze::from_edges(4, vec![(0, 1), (1, 2), (2, 3)]);
+        let mut maze = graph.clone();
+        for k in 0..3 {
+            maze.open_path_between(k, k + 1);
+        }
+
+        let grid = distances(&maze, 1).unwrap();
+        assert_eq!(grid.get_farthest(), (3, 2));
+        assert_eq!(grid.get_path_to_source(3), Some(vec![3, 2, 1]));
+
+        // Nothing but the source can be reached before any passages are carved
+        assert_eq!(distances(&graph, 1).unwrap().get_farthest(), (1, 0));
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let mut map = Map::empty((3, 3));
+        map.set_mask(Some(Mask::from_ascii("...\n.X.\n...")));
+
+        for cell in [(1, 1), (3, 0)].iter() {
+            assert_eq!(
+                distances(&map, *cell).err(),
+                Some(SolveError::OutOfBounds(*cell))
+            );
+        }
+        assert_eq!(
+            longest_path_between(&map, &[(0, 0), (1, 1)]),
+            Err(SolveError::OutOfBounds((1, 1)))
+        );
+    }
+
+    #[test]
+    fn test_diameter() {
+        // A corridor with a branch off to the side
+        let graph = GraphMaze::from_edges(6, vec![(0, 1), (1, 2), (2, 3), (1, 4), (4, 5)]);
+        let mut maze = graph.clone();
+        for (a, b) in graph.get_edges() {
+            maze.open_path_between(a, b);
+        }
+
+        assert!(distances(&maze, 0).unwrap().is_perfect(&maze));
+        assert_eq!(diameter(&maze), vec![3, 2, 1, 4, 5]);
+        assert_eq!(
+            longest_path_between(&maze, &[0, 3, 4]),
+            Ok(vec![3, 2, 1, 0])
+        );
+        assert_eq!(longest_path_between(&maze, &[]), Ok(vec![]));
+
+        // In an open room, the longest path runs between opposite corners
+        let mut map = Map::empty((3, 4));
+        map.open_all_paths();
+
+        let path = diameter(&map);
+        assert!(!distances(&map, (0, 0)).unwrap().is_perfect(&map));
+        assert_eq!(path.len(), 6);
+        assert_eq!((path[0], path[5]), ((0, 0), (2, 3)));
+    }
+
+    #[test]
+    fn test_diameter_perfect() {
+        // The two floods find a path as long as the longest of all the shortest paths
+        for seed in 0..5 {
+            let map = Map::from_seed((8, 9), seed);
+            let longest = map
+                .get_all_grid_indices()
+                .into_iter()
+                .map(|cell| distances(&map, cell).unwrap().get_max_distance())
+                .max()
+                .unwrap();
+
+            assert!(distances(&map, (0, 0)).unwrap().is_perfect(&map));
+            assert_eq!(diameter(&map).len(), longest + 1);
+        }
+    }
+
+    #[test]
+    fn test_heat_color() {
+        assert_eq!(get_heat_color(0.0), GRADIENT[0]);
+        assert_eq!(get_heat_color(0.5), GRADIENT[1]);
+        assert_eq!(get_heat_color(1.0), GRADIENT[2]);
+        assert_eq!(get_heat_color(2.0), GRADIENT[2]);
+    }
+
+    #[test]
+    fn test_to_ansi() {
+        let mut map = Map::empty((2, 3));
+        map.open_all_paths();
+
+        let ansi = distances(&map, (0, 0)).unwrap().to_ansi(&map);
+        assert_eq!(ansi.lines().count(), 2);
+        assert_eq!(ansi.matches("\x1b[48;2;").count(), 6);
+        assert!(ansi.starts_with("\x1b[48;2;68;1;84m  "));
+        assert!(ansi.lines().all(|line| line.ends_with("\x1b[0m")));
+    }
+}
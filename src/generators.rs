@@ -1,42 +1,172 @@
-use crate::map::Map;
-use rand::Rng;
-use std::thread::sleep;
-use std::time::Duration;
+use crate::disjoint_set::DisjointSet;
+use crate::graph::Graph;
+use crate::map::{Cell, Map};
+use crate::topology::{Direction, Topology};
+use rand::rngs::ThreadRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// An event emitted while a maze is being generated. Replaying the events of a
+/// generator, in order, onto an empty map reproduces the maze that it built.
+///
+/// Events refer to the nodes of whichever graph the maze is carved out of, which
+/// for a `Map` are the (row, column) indices of its cells.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GenEvent<N = (usize, usize)> {
+    // A cell became part of the maze
+    Visit(N),
+
+    // A passage was carved between two adjacent cells (or, in weave mazes, between
+    // the two cells on either side of a tunnel)
+    Carve(N, N),
+
+    // A wall was added between two adjacent cells (only emitted by generators
+    // that start from an open map, such as `RecursiveDivision`)
+    Wall(N, N),
+
+    // The generator returned to a cell that it had already visited
+    Backtrack(N),
+}
+
+/// The state of a single, in-progress run of a generator.
+pub trait Stepper<G: Graph = Map> {
+    /// Performs the next step of the algorithm on `map`, pushing the events that
+    /// describe the changes it made onto `events`. Returns `false` (without making
+    /// any changes) once the maze is finished.
+    fn step(
+        &mut self,
+        map: &mut G,
+        rng: &mut dyn RngCore,
+        events: &mut Vec<GenEvent<G::Node>>,
+    ) -> bool;
+}
 
 // The idea to move this into a trait was inspired by:
 //
 // Reference: https://github.com/CianLR/mazegen-rs
-pub trait Generator {
-    fn build(&self, map: &mut Map);
+//
+// Generators that only rely on which cells border each other work over any
+// `Graph`, while those that walk a grid row by row are only implemented for `Map`.
+pub trait Generator<G: Graph = Map> {
+    /// Prepares a new run of this generator over `map`, which can then be advanced
+    /// one step at a time.
+    fn stepper(&self, map: &G, rng: &mut dyn RngCore) -> Box<dyn Stepper<G>>;
+
+    /// Returns an iterator over the events of a new run of this generator over
+    /// `map`. The maze is only generated as far as the iterator is advanced, which
+    /// makes it possible to pause generation, or to render it frame-by-frame.
+    fn steps<'a>(&self, map: &'a mut G, rng: &'a mut dyn RngCore) -> Steps<'a, G> {
+        let stepper = self.stepper(map, rng);
+
+        Steps {
+            map,
+            rng,
+            stepper,
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Builds an entire maze in one go.
+    fn build(&self, map: &mut G, rng: &mut dyn RngCore) {
+        self.steps(map, rng).for_each(drop);
+    }
+}
+
+/// An iterator over the events of a generator as it builds a maze. Each event
+/// has already been applied to the map by the time it is yielded.
+pub struct Steps<'a, G: Graph = Map> {
+    map: &'a mut G,
+
+    rng: &'a mut dyn RngCore,
+
+    stepper: Box<dyn Stepper<G>>,
+
+    // Events produced by the last step that haven't been yielded yet
+    pending: VecDeque<GenEvent<G::Node>>,
+
+    // Whether or not the stepper has run out of work
+    finished: bool,
+}
 
-    fn each_iteration(&self, map: &Map) {
-        // Move up cursor
-        println!("\x1b[{}F", map.get_dimensions().0 + 2);
-        println!("{:?}", map);
+impl<'a, G: Graph> Steps<'a, G> {
+    /// Returns an immutable reference to the (partially generated) map.
+    pub fn get_map(&self) -> &G {
+        self.map
+    }
+}
 
-        let duration = 100;
-        sleep(Duration::from_millis(duration));
+impl<'a, G: Graph> Iterator for Steps<'a, G> {
+    type Item = GenEvent<G::Node>;
+
+    fn next(&mut self) -> Option<GenEvent<G::Node>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            if self.finished {
+                return None;
+            }
+
+            let mut events = vec![];
+            self.finished = !self.stepper.step(self.map, self.rng, &mut events);
+            self.pending.extend(events);
+        }
     }
 }
 
+/// Marks `cell` as visited and records the corresponding event.
+fn visit<G: Graph>(map: &mut G, cell: G::Node, events: &mut Vec<GenEvent<G::Node>>) {
+    map.visit(cell);
+    events.push(GenEvent::Visit(cell));
+}
+
+/// Opens the path between `to` and `from` and records the corresponding event.
+fn carve<G: Graph>(map: &mut G, to: G::Node, from: G::Node, events: &mut Vec<GenEvent<G::Node>>) {
+    map.open_path_between(to, from);
+    events.push(GenEvent::Carve(to, from));
+}
+
 /// A randomized Prim's algorithm
 pub struct Prims {}
 
-impl Generator for Prims {
+impl<G: Graph> Generator<G> for Prims {
     /// Builds a valid, "solvable" maze using a randomized version of Prim's
     /// algorithm.
     ///
     /// Reference: `https://en.wikipedia.org/wiki/Maze_generation_algorithm`
-    fn build(&self, map: &mut Map) {
-        let mut rng = rand::thread_rng();
-        let mut current = map.get_random_grid_indices();
-        map.visit(current.0, current.1);
+    fn stepper(&self, map: &G, rng: &mut dyn RngCore) -> Box<dyn Stepper<G>> {
+        Box::new(PrimsStepper {
+            start: Some(map.get_start_node(rng)),
+            frontier: vec![],
+        })
+    }
+}
+
+struct PrimsStepper<N> {
+    // The first cell of the maze (until it has been visited)
+    start: Option<N>,
 
-        let mut frontier = map.get_neighbors(current.0, current.1);
+    // Cells that border the maze (possibly more than once)
+    frontier: Vec<N>,
+}
 
-        while !frontier.is_empty() {
-            //self.each_iteration(map);
+impl<G: Graph> Stepper<G> for PrimsStepper<G::Node> {
+    fn step(
+        &mut self,
+        map: &mut G,
+        rng: &mut dyn RngCore,
+        events: &mut Vec<GenEvent<G::Node>>,
+    ) -> bool {
+        if let Some(current) = self.start.take() {
+            visit(map, current, events);
+            self.frontier = map.get_neighbors(current);
+            return true;
+        }
 
+        while !self.frontier.is_empty() {
             // Two flags: IN and FRONTIER
             //
             // Mark the first cell (set it to IN and FRONTIER)
@@ -49,81 +179,1471 @@ impl Generator for Prims {
             // 2. Add all UNVISITED neighbors to the frontier (also, avoiding ones that
             //    already have a FRONTIER flag set, i.e. they've been added before?)
 
-            current = frontier.remove(rng.gen_range(0, frontier.len()));
+            let current = self.frontier.remove(rng.gen_range(0, self.frontier.len()));
 
-            if map.get_cell(current.0, current.1).visited {
+            if map.is_visited(current) {
                 // This neighbor is already part of the maze
                 continue;
             }
-            map.visit(current.0, current.1);
+            visit(map, current, events);
 
             // remove wall between last and current
             // add unvisited neighbors to frontier
 
-            let neighbors = map.get_neighbors(current.0, current.1);
+            let neighbors = map.get_neighbors(current);
 
-            let potential_paths= map.get_visited_neighbors(current.0, current.1);
+            let potential_paths = map.get_visited_neighbors(current);
 
             // Choose one of the visited neighbors at random
             let from = potential_paths[rng.gen_range(0, potential_paths.len())];
             let to = current;
-            map.open_path_between(to, from);
-
-
+            carve(map, to, from, events);
 
-
-            frontier.extend_from_slice(&neighbors);
+            self.frontier.extend_from_slice(&neighbors);
+            return true;
         }
+
+        false
     }
 }
 
 /// A recursive backtracking algorithm
 pub struct Backtracking {}
 
-impl Generator for Backtracking {
+impl<G: Graph> Generator<G> for Backtracking {
     /// A method for randomly generating mazes.
     ///
     /// Reference: `https://en.wikipedia.org/wiki/Maze_generation_algorithm`
-    fn build(&self, map: &mut Map) {
-        let mut rng = rand::thread_rng();
-        let mut current = map.get_random_grid_indices();
-        map.visit(current.0, current.1);
+    fn stepper(&self, map: &G, rng: &mut dyn RngCore) -> Box<dyn Stepper<G>> {
+        let current = map.get_start_node(rng);
+
+        Box::new(BacktrackingStepper {
+            current,
+            started: false,
+            stack: vec![],
+        })
+    }
+}
 
-        // Set up a stack for backtracking
-        let mut stack: Vec<(usize, usize)> = vec![];
+struct BacktrackingStepper<N> {
+    // The cell that the algorithm is currently carving from
+    current: N,
 
-        'outer: loop {
-            let potential_paths = map.get_unvisited_neighbors(current.0, current.1);
+    // Whether or not the first cell has been visited
+    started: bool,
 
-            if potential_paths.is_empty() {
-                'inner: loop {
-                    if let Some(indices) = stack.pop() {
-                        // Work backwards and find the first cell that has at least one "closed" off wall
-                        if !map.get_cell(indices.0, indices.1).is_completely_open() {
-                            current = indices;
-                            break 'inner;
-                        }
-                    } else {
-                        // The stack is empty - end the recursion
-                        break 'outer;
+    // The stack used for backtracking
+    stack: Vec<N>,
+}
+
+impl<G: Graph> Stepper<G> for BacktrackingStepper<G::Node> {
+    fn step(
+        &mut self,
+        map: &mut G,
+        rng: &mut dyn RngCore,
+        events: &mut Vec<GenEvent<G::Node>>,
+    ) -> bool {
+        if !self.started {
+            self.started = true;
+            visit(map, self.current, events);
+
+            // The first cell may have to be revisited in the backwards pass, too
+            self.stack.push(self.current);
+            return true;
+        }
+
+        let potential_paths = map.get_unvisited_neighbors(self.current);
+
+        if potential_paths.is_empty() {
+            loop {
+                if let Some(&indices) = self.stack.last() {
+                    // Work backwards and find the first cell that can still be carved from
+                    // (it stays on the stack, since it may have more than one way out)
+                    if !map.get_unvisited_neighbors(indices).is_empty() {
+                        // We have a new "starting" cell - go back to the beginning of the algorithm
+                        self.current = indices;
+                        events.push(GenEvent::Backtrack(indices));
+                        return true;
+                    }
+                    self.stack.pop();
+                } else {
+                    // The stack is empty - end the recursion
+                    return false;
+                }
+            }
+        }
+
+        // Choose one of the unvisited neighbors at random
+        let from = potential_paths[rng.gen_range(0, potential_paths.len())];
+        let to = self.current;
+        carve(map, to, from, events);
+
+        // Mark the current cell as `visited` and recurse
+        self.current = from;
+        visit(map, self.current, events);
+
+        // Add this cell to the stack - it may be visited again in the backwards pass
+        self.stack.push(self.current);
+        true
+    }
+}
+
+/// A randomized Kruskal's algorithm
+pub struct Kruskal {}
+
+impl<G: Graph> Generator<G> for Kruskal {
+    /// Builds a maze by visiting every interior wall in a random order and
+    /// removing it whenever the two cells it separates are not already
+    /// connected. A disjoint-set keeps track of which cells are connected.
+    ///
+    /// In weave mazes, crossings are scattered over the map before any walls are
+    /// removed: each one is a short corridor with a tunnel running underneath it,
+    /// and the cells it connects are merged into the same sets up front.
+    ///
+    /// Reference: `https://en.wikipedia.org/wiki/Maze_generation_algorithm`
+    /// Reference: `http://weblog.jamisbuck.org/2011/3/17/weave-mazes-your-take`
+    fn stepper(&self, map: &G, rng: &mut dyn RngCore) -> Box<dyn Stepper<G>> {
+        let nodes = map.get_nodes();
+
+        // Gather every wall exactly once, as a pair of adjacent cells
+        let mut walls = vec![];
+        for current in nodes.iter() {
+            for neighbor in map.get_neighbors(*current) {
+                if *current < neighbor {
+                    walls.push((*current, neighbor));
+                }
+            }
+        }
+        walls.shuffle(rng);
+
+        // Every cell that a corridor and a tunnel could cross at (only in weave
+        // mazes) is a potential crossing, again in a random order
+        let mut crossings: Vec<G::Node> = nodes
+            .iter()
+            .cloned()
+            .filter(|node| map.get_crossings(*node).len() == 2)
+            .collect();
+        crossings.shuffle(rng);
+
+        Box::new(KruskalStepper {
+            walls,
+            crossings,
+            // Each cell starts out in its own set
+            sets: DisjointSet::new(nodes.len()),
+            indices: nodes
+                .iter()
+                .enumerate()
+                .map(|(k, node)| (*node, k))
+                .collect(),
+            cells: nodes,
+        })
+    }
+}
+
+struct KruskalStepper<N> {
+    // The walls that haven't been considered yet, in a random order
+    walls: Vec<(N, N)>,
+
+    // The cells that haven't been considered as crossings yet (in weave mazes)
+    crossings: Vec<N>,
+
+    // Which cells are connected to one another
+    sets: DisjointSet,
+
+    // The element of `sets` that corresponds to each cell
+    indices: HashMap<N, usize>,
+
+    // Every cell, so that cells without any walls to remove (like the only cell
+    // of a 1x1 map) can still be visited once all of the walls are gone
+    cells: Vec<N>,
+}
+
+impl<N: Copy + Eq + Hash> KruskalStepper<N> {
+    /// Joins the sets that contain `a` and `b`, returning `false` if they were
+    /// already connected.
+    fn union(&mut self, a: N, b: N) -> bool {
+        self.sets.union(self.indices[&a], self.indices[&b])
+    }
+
+    /// Tries to place a crossing at `center`: a corridor through `center`, with a
+    /// tunnel running underneath it. This only works if `center` and its four
+    /// neighbors are all still untouched. Returns `true` if a crossing was placed.
+    fn cross<G: Graph<Node = N>>(
+        &mut self,
+        map: &mut G,
+        center: N,
+        rng: &mut dyn RngCore,
+        events: &mut Vec<GenEvent<N>>,
+    ) -> bool {
+        let pairs = map.get_crossings(center);
+
+        if pairs.len() < 2
+            || map.is_visited(center)
+            || pairs.iter().flatten().any(|cell| map.is_visited(*cell))
+        {
+            return false;
+        }
+
+        // Either passage can be the corridor, with the other one as the tunnel
+        let (corridor, tunnel) = if rng.gen() {
+            (pairs[0], pairs[1])
+        } else {
+            (pairs[1], pairs[0])
+        };
+
+        visit(map, center, events);
+        for cell in corridor.iter().chain(tunnel.iter()) {
+            visit(map, *cell, events);
+        }
+        for end in corridor.iter() {
+            carve(map, center, *end, events);
+        }
+        carve(map, tunnel[0], tunnel[1], events);
+
+        self.union(center, corridor[0]);
+        self.union(center, corridor[1]);
+        self.union(tunnel[0], tunnel[1]);
+
+        true
+    }
+}
+
+impl<G: Graph> Stepper<G> for KruskalStepper<G::Node> {
+    fn step(
+        &mut self,
+        map: &mut G,
+        rng: &mut dyn RngCore,
+        events: &mut Vec<GenEvent<G::Node>>,
+    ) -> bool {
+        while let Some(center) = self.crossings.pop() {
+            if self.cross(map, center, rng, events) {
+                return true;
+            }
+        }
+
+        while let Some((to, from)) = self.walls.pop() {
+            // Walls that a tunnel passes through (i.e. that are only closed on
+            // one side) are left alone
+            let closed = map.get_closed_neighbors(to).contains(&from)
+                && map.get_closed_neighbors(from).contains(&to);
+
+            // Only remove this wall if it joins two distinct sets, otherwise
+            // we would introduce a loop
+            if closed && self.union(to, from) {
+                carve(map, to, from, events);
+                for cell in [to, from].iter() {
+                    if !map.is_visited(*cell) {
+                        visit(map, *cell, events);
+                    }
+                }
+                return true;
+            }
+        }
+
+        while let Some(cell) = self.cells.pop() {
+            if !map.is_visited(cell) {
+                visit(map, cell, events);
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/// The shared state of the random walk based generators: an Aldous-Broder random
+/// walk that runs until `target` cells have been visited, followed by loop-erased
+/// random walks (Wilson's algorithm) that visit the rest of the cells.
+struct RandomWalkStepper<N> {
+    // The first cell of the maze (until it has been visited)
+    start: Option<N>,
+
+    // The current position of the Aldous-Broder random walk
+    current: N,
+
+    // The number of visited cells
+    visited: usize,
+
+    // The number of cells to visit before switching to Wilson's algorithm
+    target: usize,
+
+    // The unvisited cells (only gathered once Wilson's algorithm begins)
+    unvisited: Option<Vec<N>>,
+
+    // The position of each cell along the current loop-erased walk
+    position: HashMap<N, usize>,
+
+    // The cells that can be reached from the first cell, which are the only ones
+    // that the walks will ever find
+    reachable: HashSet<N>,
+}
+
+impl<N: Copy + Eq + Hash> RandomWalkStepper<N> {
+    fn new<G: Graph<Node = N>>(map: &G, rng: &mut dyn RngCore, target: usize) -> Self {
+        let start = map.get_start_node(rng);
+
+        // If the graph falls apart into several pieces, only the piece containing
+        // the first cell can be carved, so the walks stop once it is done
+        let mut reachable = HashSet::new();
+        let mut frontier = vec![start];
+        reachable.insert(start);
+        while let Some(current) = frontier.pop() {
+            for neighbor in map.get_neighbors(current) {
+                if reachable.insert(neighbor) {
+                    frontier.push(neighbor);
+                }
+            }
+        }
+
+        RandomWalkStepper {
+            start: Some(start),
+            current: start,
+            visited: 0,
+            target: target.clamp(1, reachable.len()),
+            unvisited: None,
+            position: HashMap::new(),
+            reachable,
+        }
+    }
+
+    /// Continues the Aldous-Broder random walk until it steps into an unvisited
+    /// cell, and carves the wall that it came through.
+    fn walk<G: Graph<Node = N>>(
+        &mut self,
+        map: &mut G,
+        rng: &mut dyn RngCore,
+        events: &mut Vec<GenEvent<N>>,
+    ) {
+        loop {
+            let neighbors = map.get_neighbors(self.current);
+            let next = neighbors[rng.gen_range(0, neighbors.len())];
+            let previous = self.current;
+            self.current = next;
+
+            if !map.is_visited(next) {
+                carve(map, previous, next, events);
+                visit(map, next, events);
+                self.visited += 1;
+                return;
+            }
+        }
+    }
+
+    /// Performs a single loop-erased random walk from an unvisited cell until the
+    /// walk runs into the visited part of the maze, then carves the (loop-free) walk.
+    fn loop_erased_walk<G: Graph<Node = N>>(
+        &mut self,
+        map: &mut G,
+        rng: &mut dyn RngCore,
+        start: N,
+        events: &mut Vec<GenEvent<N>>,
+    ) {
+        let mut walk = vec![start];
+        self.position.insert(start, 0);
+
+        let mut current = start;
+
+        while !map.is_visited(current) {
+            let neighbors = map.get_neighbors(current);
+            let next = neighbors[rng.gen_range(0, neighbors.len())];
+
+            if let Some(&loop_start) = self.position.get(&next) {
+                // The walk crossed itself: erase the loop that was just formed
+                for erased in walk.drain(loop_start + 1..) {
+                    self.position.remove(&erased);
+                }
+            } else {
+                self.position.insert(next, walk.len());
+                walk.push(next);
+            }
+
+            current = next;
+        }
+
+        // Carve the walk backwards from the visited cell that it ends at, so that
+        // every carved passage leads out of the maze
+        for pair in walk.windows(2).rev() {
+            carve(map, pair[1], pair[0], events);
+            visit(map, pair[0], events);
+        }
+        self.position.clear();
+    }
+}
+
+impl<G: Graph> Stepper<G> for RandomWalkStepper<G::Node> {
+    fn step(
+        &mut self,
+        map: &mut G,
+        rng: &mut dyn RngCore,
+        events: &mut Vec<GenEvent<G::Node>>,
+    ) -> bool {
+        if let Some(current) = self.start.take() {
+            visit(map, current, events);
+            self.visited = 1;
+            return true;
+        }
+
+        if self.visited < self.target {
+            self.walk(map, rng, events);
+            return true;
+        }
+
+        let mut unvisited = match self.unvisited.take() {
+            Some(unvisited) => unvisited,
+            None => map
+                .get_nodes()
+                .into_iter()
+                .filter(|node| !map.is_visited(*node) && self.reachable.contains(node))
+                .collect(),
+        };
+        unvisited.retain(|node| !map.is_visited(*node));
+
+        if unvisited.is_empty() {
+            return false;
+        }
+
+        let start = unvisited[rng.gen_range(0, unvisited.len())];
+        self.loop_erased_walk(map, rng, start, events);
+        self.unvisited = Some(unvisited);
+        true
+    }
+}
+
+/// The Aldous-Broder algorithm
+pub struct AldousBroder {}
+
+impl<G: Graph> Generator<G> for AldousBroder {
+    /// Builds an unbiased maze (a uniform spanning tree) by performing a random
+    /// walk over the grid, carving a passage whenever the walk enters a cell for
+    /// the first time. This can take a long time to finish, since the walk has
+    /// to stumble onto the last few unvisited cells by chance.
+    ///
+    /// Reference: `https://en.wikipedia.org/wiki/Maze_generation_algorithm`
+    fn stepper(&self, map: &G, rng: &mut dyn RngCore) -> Box<dyn Stepper<G>> {
+        let target = map.get_node_count();
+        Box::new(RandomWalkStepper::new(map, rng, target))
+    }
+}
+
+/// Wilson's algorithm
+pub struct Wilson {}
+
+impl<G: Graph> Generator<G> for Wilson {
+    /// Builds an unbiased maze (a uniform spanning tree) using loop-erased random
+    /// walks. The first walks are long, since they have to find the single
+    /// visited cell, but later walks quickly run into the growing maze.
+    ///
+    /// Reference: `https://en.wikipedia.org/wiki/Maze_generation_algorithm`
+    fn stepper(&self, map: &G, rng: &mut dyn RngCore) -> Box<dyn Stepper<G>> {
+        Box::new(RandomWalkStepper::new(map, rng, 1))
+    }
+}
+
+/// A hybrid of the Aldous-Broder algorithm and Wilson's algorithm
+pub struct AldousBroderWilson {
+    // The fraction of cells (between 0 and 1) that should be visited by the
+    // Aldous-Broder random walk before switching to Wilson's algorithm
+    pub switch_at: f32,
+}
+
+impl<G: Graph> Generator<G> for AldousBroderWilson {
+    /// Builds an unbiased maze (a uniform spanning tree) by starting with an
+    /// Aldous-Broder random walk, which is fast while most cells are unvisited,
+    /// and finishing with Wilson's algorithm, which is fast once most cells are
+    /// visited.
+    fn stepper(&self, map: &G, rng: &mut dyn RngCore) -> Box<dyn Stepper<G>> {
+        let cells = map.get_node_count();
+        let target = (self.switch_at.clamp(0.0, 1.0) * cells as f32).ceil() as usize;
+
+        Box::new(RandomWalkStepper::new(map, rng, target))
+    }
+}
+
+/// The per-row bookkeeping of Eller's algorithm: which set each column of the
+/// current row belongs to. Only a single row is ever stored.
+struct EllerState {
+    // The set that each column of the current row belongs to (or `None` if
+    // the cell is not yet part of any set)
+    sets: Vec<Option<usize>>,
+
+    // The next unused set identifier
+    next_set: usize,
+}
+
+impl EllerState {
+    fn new(width: usize) -> EllerState {
+        EllerState {
+            sets: vec![None; width],
+            next_set: 0,
+        }
+    }
+
+    /// Carves the current row and prepares the sets for the row below it. Returns
+    /// two vectors of flags: whether each cell is open to the east and whether each
+    /// cell is open to the south. If `last` is `true`, all remaining sets are joined
+    /// horizontally and nothing is carved to the south.
+    fn carve_row(&mut self, rng: &mut dyn RngCore, last: bool) -> (Vec<bool>, Vec<bool>) {
+        let width = self.sets.len();
+
+        // Cells that weren't carved into from above start out in their own set
+        for set in self.sets.iter_mut() {
+            if set.is_none() {
+                *set = Some(self.next_set);
+                self.next_set += 1;
+            }
+        }
+
+        // Randomly join adjacent cells that belong to different sets
+        let mut east = vec![false; width];
+        for (j, open) in east.iter_mut().enumerate().take(width.saturating_sub(1)) {
+            let (a, b) = (self.sets[j], self.sets[j + 1]);
+
+            if a != b && (last || rng.gen_bool(0.5)) {
+                *open = true;
+
+                for set in self.sets.iter_mut() {
+                    if *set == b {
+                        *set = a;
+                    }
+                }
+            }
+        }
+
+        let mut south = vec![false; width];
+        if !last {
+            // Every set must extend downwards at least once, otherwise it would be
+            // cut off from the rest of the maze
+            let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+            for (j, set) in self.sets.iter().enumerate() {
+                members.entry(set.unwrap()).or_default().push(j);
+            }
+
+            for columns in members.values_mut() {
+                columns.shuffle(rng);
+
+                let count = rng.gen_range(1, columns.len() + 1);
+                for j in columns.iter().take(count) {
+                    south[*j] = true;
+                }
+            }
+        }
+
+        // Only the cells that were carved into keep their set in the next row
+        for (set, down) in self.sets.iter_mut().zip(south.iter()) {
+            if !down {
+                *set = None;
+            }
+        }
+
+        (east, south)
+    }
+}
+
+/// Eller's algorithm
+pub struct Eller {}
+
+impl Generator for Eller {
+    /// Builds a maze one row at a time, keeping track of which cells in the current
+    /// row are connected (through previous rows) and carving at least one passage
+    /// down from each group of connected cells.
+    ///
+    /// Only unmasked `Square` and `Hex` maps are supported, since each row is carved
+    /// east and south by column index, without checking which cells are part of the map.
+    ///
+    /// Reference: `http://weblog.jamisbuck.org/2010/12/29/maze-generation-eller-s-algorithm`
+    fn stepper(&self, map: &Map, _: &mut dyn RngCore) -> Box<dyn Stepper> {
+        assert!(
+            matches!(map.get_topology(), Topology::Square | Topology::Hex),
+            "Eller's algorithm only supports square and hexagonal maps"
+        );
+        assert!(
+            map.get_mask().is_none(),
+            "Eller's algorithm doesn't support masked maps"
+        );
+        let (_, cols) = map.get_dimensions();
+
+        Box::new(EllerStepper {
+            state: EllerState::new(cols),
+            row: 0,
+        })
+    }
+}
+
+struct EllerStepper {
+    state: EllerState,
+
+    // The next row to carve
+    row: usize,
+}
+
+impl Stepper for EllerStepper {
+    fn step(&mut self, map: &mut Map, rng: &mut dyn RngCore, events: &mut Vec<GenEvent>) -> bool {
+        let (rows, cols) = map.get_dimensions();
+        let i = self.row;
+
+        if i == rows {
+            return false;
+        }
+
+        let (east, south) = self.state.carve_row(rng, i == rows - 1);
+
+        for j in 0..cols {
+            visit(map, (i, j), events);
+        }
+        for j in 0..cols {
+            if east[j] {
+                carve(map, (i, j), (i, j + 1), events);
+            }
+            if south[j] {
+                carve(map, (i, j), (i + 1, j), events);
+            }
+        }
+
+        self.row += 1;
+        true
+    }
+}
+
+/// A stream of maze rows generated with Eller's algorithm. Each row is yielded as
+/// soon as it is finished, so the full maze never has to be held in memory. The
+/// stream is either endless, in which case `finish` produces the final row that
+/// closes off the maze, or has a fixed height, in which case its last row does.
+pub struct EllerRows<R: RngCore = ThreadRng> {
+    state: EllerState,
+
+    // Whether or not each cell of the next row is open to the north
+    north: Vec<bool>,
+
+    // The number of rows left to yield, including the final row (or `None` if
+    // the stream is endless)
+    remaining: Option<usize>,
+
+    rng: R,
+}
+
+impl EllerRows {
+    /// Constructs a new, endless stream of rows, each of which is `width` cells wide.
+    pub fn new(width: usize) -> EllerRows {
+        EllerRows::from_rng(width, rand::thread_rng())
+    }
+
+    /// Constructs a new stream of `height` rows, each of which is `width` cells wide.
+    /// The last row closes off the maze, so the stream can be passed straight to
+    /// `map::save_ascii_rows`.
+    pub fn with_height(width: usize, height: usize) -> EllerRows {
+        EllerRows::from_rng_with_height(width, height, rand::thread_rng())
+    }
+}
+
+impl<R: RngCore> EllerRows<R> {
+    /// Constructs a new, endless stream of rows, each of which is `width` cells wide,
+    /// that draws its random numbers from `rng`. Two streams constructed with
+    /// identically seeded generators will produce the same rows.
+    pub fn from_rng(width: usize, rng: R) -> EllerRows<R> {
+        assert!(width > 0, "Rows must contain at least one cell");
+
+        EllerRows {
+            state: EllerState::new(width),
+            north: vec![false; width],
+            remaining: None,
+            rng,
+        }
+    }
+
+    /// Constructs a new stream of `height` rows, like `with_height`, that draws its
+    /// random numbers from `rng`. The rows are the same as those of the `Eller`
+    /// generator on a map of the same size, given an identically seeded generator.
+    pub fn from_rng_with_height(width: usize, height: usize, rng: R) -> EllerRows<R> {
+        assert!(height > 0, "A maze must contain at least one row");
+
+        EllerRows {
+            remaining: Some(height),
+            ..EllerRows::from_rng(width, rng)
+        }
+    }
+
+    /// Returns the final row of an endless stream, which joins all remaining sets so
+    /// that every row yielded so far is connected. A stream with a fixed height
+    /// yields its final row itself.
+    pub fn finish(mut self) -> Vec<Cell> {
+        assert!(
+            self.remaining.is_none(),
+            "Attempting to finish a stream of rows with a fixed height"
+        );
+        self.carve_row(true)
+    }
+
+    fn carve_row(&mut self, last: bool) -> Vec<Cell> {
+        let (east, south) = self.state.carve_row(&mut self.rng, last);
+
+        let row = (0..east.len())
+            .map(|j| {
+                let mut cell = Cell::new();
+                cell.visited = true;
+                cell.set_open(Direction::North, self.north[j]);
+                cell.set_open(Direction::South, south[j]);
+                cell.set_open(Direction::East, east[j]);
+                cell.set_open(Direction::West, j > 0 && east[j - 1]);
+                cell
+            })
+            .collect();
+
+        self.north = south;
+        row
+    }
+}
+
+impl<R: RngCore> Iterator for EllerRows<R> {
+    type Item = Vec<Cell>;
+
+    fn next(&mut self) -> Option<Vec<Cell>> {
+        match self.remaining {
+            None => Some(self.carve_row(false)),
+            Some(0) => None,
+            Some(remaining) => {
+                self.remaining = Some(remaining - 1);
+                Some(self.carve_row(remaining == 1))
+            }
+        }
+    }
+}
+
+/// A recursive division algorithm
+pub struct RecursiveDivision {}
+
+impl Generator for RecursiveDivision {
+    /// Builds a maze by starting with a single open room and repeatedly dividing it
+    /// in two with a wall that has exactly one gap in it, until every room is a
+    /// single cell wide. Rather than actually recursing, the rooms that still need
+    /// to be divided are kept on a stack.
+    ///
+    /// Reference: `https://en.wikipedia.org/wiki/Maze_generation_algorithm`
+    fn stepper(&self, map: &Map, _: &mut dyn RngCore) -> Box<dyn Stepper> {
+        assert_eq!(
+            map.get_topology(),
+            Topology::Square,
+            "Recursive division only supports square cells"
+        );
+        assert!(
+            map.get_mask().is_none(),
+            "Recursive division doesn't support masked maps"
+        );
+
+        // Each room is stored as (top row, left column, height, width)
+        let (rows, cols) = map.get_dimensions();
+
+        Box::new(RecursiveDivisionStepper {
+            opened: false,
+            rooms: vec![(0, 0, rows, cols)],
+        })
+    }
+}
+
+struct RecursiveDivisionStepper {
+    // Whether or not the map has been turned into a single open room
+    opened: bool,
+
+    // The rooms that still need to be divided
+    rooms: Vec<(usize, usize, usize, usize)>,
+}
+
+impl RecursiveDivisionStepper {
+    /// Closes the path between `to` and `from` and records the corresponding event.
+    fn wall(map: &mut Map, to: (usize, usize), from: (usize, usize), events: &mut Vec<GenEvent>) {
+        map.close_path_between(to, from);
+        events.push(GenEvent::Wall(to, from));
+    }
+}
+
+impl Stepper for RecursiveDivisionStepper {
+    fn step(&mut self, map: &mut Map, rng: &mut dyn RngCore, events: &mut Vec<GenEvent>) -> bool {
+        if !self.opened {
+            self.opened = true;
+            map.open_all_paths();
+
+            for current in map.get_all_grid_indices() {
+                events.push(GenEvent::Visit(current));
+                for neighbor in map.get_neighbors(current.0, current.1) {
+                    if current < neighbor {
+                        events.push(GenEvent::Carve(current, neighbor));
+                    }
+                }
+            }
+            return true;
+        }
+
+        while let Some((top, left, height, width)) = self.rooms.pop() {
+            if height < 2 || width < 2 {
+                continue;
+            }
+
+            // Divide across the longer side of the room, which avoids long, thin rooms
+            let horizontal = if height == width {
+                rng.gen_bool(0.5)
+            } else {
+                height > width
+            };
+
+            if horizontal {
+                // The wall runs below row `top + at`, with a gap at column `left + gap`
+                let at = rng.gen_range(0, height - 1);
+                let gap = rng.gen_range(0, width);
+
+                for j in (left..left + width).filter(|j| *j != left + gap) {
+                    Self::wall(map, (top + at, j), (top + at + 1, j), events);
+                }
+
+                self.rooms.push((top, left, at + 1, width));
+                self.rooms
+                    .push((top + at + 1, left, height - at - 1, width));
+            } else {
+                // The wall runs to the right of column `left + at`, with a gap at row `top + gap`
+                let at = rng.gen_range(0, width - 1);
+                let gap = rng.gen_range(0, height);
+
+                for i in (top..top + height).filter(|i| *i != top + gap) {
+                    Self::wall(map, (i, left + at), (i, left + at + 1), events);
+                }
+
+                self.rooms.push((top, left, height, at + 1));
+                self.rooms
+                    .push((top, left + at + 1, height, width - at - 1));
+            }
+
+            return true;
+        }
+
+        false
+    }
+}
+
+/// The policy used by the growing tree algorithm to choose which active cell to
+/// grow the maze from next.
+#[derive(Clone, Debug)]
+pub enum Selection {
+    // Always choose the most recently added cell (behaves like `Backtracking`)
+    Newest,
+
+    // Always choose the least recently added cell
+    Oldest,
+
+    // Choose a cell at random (behaves like `Prims`)
+    Random,
+
+    // Choose between several policies at random, in proportion to their weights:
+    // for example, `[(Newest, 3.0), (Random, 1.0)]` picks the newest cell 75% of
+    // the time and a random cell 25% of the time
+    Weighted(Vec<(Selection, f32)>),
+}
+
+impl Selection {
+    /// Returns the index of the cell to choose from a list of `len` active cells,
+    /// which are ordered from oldest to newest.
+    fn choose(&self, len: usize, rng: &mut dyn RngCore) -> usize {
+        match self {
+            Selection::Newest => len - 1,
+            Selection::Oldest => 0,
+            Selection::Random => rng.gen_range(0, len),
+            Selection::Weighted(policies) => {
+                let total: f32 = policies.iter().map(|(_, weight)| weight).sum();
+                let mut target = rng.gen::<f32>() * total;
+
+                for (policy, weight) in policies.iter() {
+                    if target < *weight {
+                        return policy.choose(len, rng);
                     }
+                    target -= weight;
+                }
+
+                // Only reachable because of rounding errors (or if there are no policies)
+                match policies.last() {
+                    Some((policy, _)) => policy.choose(len, rng),
+                    None => len - 1,
                 }
+            }
+        }
+    }
+}
+
+/// A growing tree algorithm
+pub struct GrowingTree {
+    // How to choose the next cell to grow the maze from
+    pub selection: Selection,
+}
+
+impl<G: Graph> Generator<G> for GrowingTree {
+    /// Builds a maze by maintaining a list of "active" cells, repeatedly choosing
+    /// one of them (according to `selection`) and carving a passage to one of its
+    /// unvisited neighbors. Cells without any unvisited neighbors are removed from
+    /// the list.
+    ///
+    /// Reference: `http://weblog.jamisbuck.org/2011/1/27/maze-generation-growing-tree-algorithm`
+    fn stepper(&self, map: &G, rng: &mut dyn RngCore) -> Box<dyn Stepper<G>> {
+        Box::new(GrowingTreeStepper {
+            selection: self.selection.clone(),
+            start: Some(map.get_start_node(rng)),
+            active: vec![],
+        })
+    }
+}
 
-                // We have a new "starting" cell - go back to the beginning of the algorithm
+struct GrowingTreeStepper<N> {
+    selection: Selection,
+
+    // The first cell of the maze (until it has been visited)
+    start: Option<N>,
+
+    // The active cells, ordered from oldest to newest
+    active: Vec<N>,
+}
+
+impl<G: Graph> Stepper<G> for GrowingTreeStepper<G::Node> {
+    fn step(
+        &mut self,
+        map: &mut G,
+        rng: &mut dyn RngCore,
+        events: &mut Vec<GenEvent<G::Node>>,
+    ) -> bool {
+        if let Some(current) = self.start.take() {
+            visit(map, current, events);
+            self.active.push(current);
+            return true;
+        }
+
+        while !self.active.is_empty() {
+            let idx = self.selection.choose(self.active.len(), rng);
+            let current = self.active[idx];
+
+            let potential_paths = map.get_unvisited_neighbors(current);
+
+            if potential_paths.is_empty() {
+                // This cell is finished: keep the rest of the list in order
+                self.active.remove(idx);
                 continue;
             }
 
             // Choose one of the unvisited neighbors at random
-            let from = potential_paths[rng.gen_range(0, potential_paths.len())];
-            let to = current;
-            map.open_path_between(to, from);
+            let next = potential_paths[rng.gen_range(0, potential_paths.len())];
+            carve(map, current, next, events);
+            visit(map, next, events);
+
+            self.active.push(next);
+            return true;
+        }
+
+        false
+    }
+}
+
+/// The hunt-and-kill algorithm
+pub struct HuntAndKill {}
+
+impl<G: Graph> Generator<G> for HuntAndKill {
+    /// Builds a maze by performing a random walk that only steps into unvisited
+    /// cells (the "kill" phase). When the walk gets stuck, the map is scanned for
+    /// the first unvisited cell that borders the maze, which is connected to it
+    /// and becomes the start of the next walk (the "hunt" phase). Unlike
+    /// `Backtracking`, no stack is required.
+    ///
+    /// Reference: `http://weblog.jamisbuck.org/2011/1/24/maze-generation-hunt-and-kill-algorithm`
+    fn stepper(&self, map: &G, rng: &mut dyn RngCore) -> Box<dyn Stepper<G>> {
+        let current = map.get_start_node(rng);
+
+        Box::new(HuntAndKillStepper {
+            current,
+            started: false,
+        })
+    }
+}
+
+struct HuntAndKillStepper<N> {
+    // The current position of the random walk
+    current: N,
+
+    // Whether or not the first cell has been visited
+    started: bool,
+}
+
+impl<G: Graph> Stepper<G> for HuntAndKillStepper<G::Node> {
+    fn step(
+        &mut self,
+        map: &mut G,
+        rng: &mut dyn RngCore,
+        events: &mut Vec<GenEvent<G::Node>>,
+    ) -> bool {
+        if !self.started {
+            self.started = true;
+            visit(map, self.current, events);
+            return true;
+        }
+
+        let potential_paths = map.get_unvisited_neighbors(self.current);
 
-            // Mark the current cell as `visited` and recurse
-            current = from;
-            map.visit(current.0, current.1);
+        if !potential_paths.is_empty() {
+            // Kill: walk to one of the unvisited neighbors at random
+            let next = potential_paths[rng.gen_range(0, potential_paths.len())];
+            carve(map, self.current, next, events);
+            visit(map, next, events);
 
-            // Add this cell to the stack - it may be visited again in the backwards pass
-            stack.push(current);
+            self.current = next;
+            return true;
         }
+
+        // Hunt: find the first unvisited cell that is adjacent to the maze
+        let hunted = map
+            .get_nodes()
+            .into_iter()
+            .find(|node| !map.is_visited(*node) && !map.get_visited_neighbors(*node).is_empty());
+
+        match hunted {
+            Some(next) => {
+                let potential_paths = map.get_visited_neighbors(next);
+                let from = potential_paths[rng.gen_range(0, potential_paths.len())];
+                carve(map, from, next, events);
+                visit(map, next, events);
+
+                self.current = next;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A binary tree algorithm
+pub struct BinaryTree {}
+
+impl Generator for BinaryTree {
+    /// Builds a maze by carving a passage either north or east from every cell,
+    /// chosen at random. Each cell is handled independently, so no bookkeeping
+    /// is required, but the resulting mazes have a strong diagonal bias and two
+    /// unbroken corridors along the top and right edges of the map.
+    ///
+    /// Each passage runs to the cell one row up or one column right, whatever the
+    /// shape of the map, so only unmasked `Square` and `Hex` maps are supported.
+    ///
+    /// Reference: `http://weblog.jamisbuck.org/2011/2/1/maze-generation-binary-tree-algorithm`
+    fn stepper(&self, map: &Map, _: &mut dyn RngCore) -> Box<dyn Stepper> {
+        assert!(
+            matches!(map.get_topology(), Topology::Square | Topology::Hex),
+            "The binary tree algorithm only supports square and hexagonal maps"
+        );
+        assert!(
+            map.get_mask().is_none(),
+            "The binary tree algorithm doesn't support masked maps"
+        );
+
+        Box::new(BinaryTreeStepper { next: 0 })
+    }
+}
+
+struct BinaryTreeStepper {
+    // The absolute index of the next cell to process
+    next: usize,
+}
+
+impl Stepper for BinaryTreeStepper {
+    fn step(&mut self, map: &mut Map, rng: &mut dyn RngCore, events: &mut Vec<GenEvent>) -> bool {
+        if self.next == map.get_terrain().len() {
+            return false;
+        }
+
+        let (_, cols) = map.get_dimensions();
+        let (i, j) = map.absolute_to_grid_indices(self.next);
+        self.next += 1;
+
+        visit(map, (i, j), events);
+
+        let mut potential_paths = vec![];
+        if i > 0 {
+            potential_paths.push((i - 1, j));
+        }
+        if j < cols - 1 {
+            potential_paths.push((i, j + 1));
+        }
+
+        // The north-east corner is the root of the tree
+        if !potential_paths.is_empty() {
+            let next = potential_paths[rng.gen_range(0, potential_paths.len())];
+            carve(map, (i, j), next, events);
+        }
+
+        true
+    }
+}
+
+/// The sidewinder algorithm
+pub struct Sidewinder {}
+
+impl Generator for Sidewinder {
+    /// Builds a maze one row at a time by carving runs of cells to the east, and
+    /// randomly closing each run by carving north from one of its cells. Only the
+    /// current run needs to be stored. The top row is always a single corridor.
+    ///
+    /// Requires an unmasked `Square` or `Hex` map, because runs are carved east along
+    /// each row and closed off by carving north into the row above, by grid index.
+    ///
+    /// Reference: `http://weblog.jamisbuck.org/2011/2/3/maze-generation-sidewinder-algorithm`
+    fn stepper(&self, map: &Map, _: &mut dyn RngCore) -> Box<dyn Stepper> {
+        assert!(
+            matches!(map.get_topology(), Topology::Square | Topology::Hex),
+            "The sidewinder algorithm only supports square and hexagonal maps"
+        );
+        assert!(
+            map.get_mask().is_none(),
+            "The sidewinder algorithm doesn't support masked maps"
+        );
+
+        Box::new(SidewinderStepper {
+            next: 0,
+            run: vec![],
+        })
+    }
+}
+
+struct SidewinderStepper {
+    // The absolute index of the next cell to process
+    next: usize,
+
+    // The columns of the current run of cells
+    run: Vec<usize>,
+}
+
+impl Stepper for SidewinderStepper {
+    fn step(&mut self, map: &mut Map, rng: &mut dyn RngCore, events: &mut Vec<GenEvent>) -> bool {
+        if self.next == map.get_terrain().len() {
+            return false;
+        }
+
+        let (_, cols) = map.get_dimensions();
+        let (i, j) = map.absolute_to_grid_indices(self.next);
+        self.next += 1;
+
+        visit(map, (i, j), events);
+        self.run.push(j);
+
+        let at_east_border = j == cols - 1;
+        let at_north_border = i == 0;
+
+        if at_east_border || (!at_north_border && rng.gen_bool(0.5)) {
+            // Close out the run by carving north from a random member
+            if !at_north_border {
+                let member = self.run[rng.gen_range(0, self.run.len())];
+                carve(map, (i, member), (i - 1, member), events);
+            }
+            self.run.clear();
+        } else {
+            carve(map, (i, j), (i, j + 1), events);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mask::Mask;
+    use crate::search::breadth_first;
+    use crate::topology::Wrap;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    /// The generators that can carve a maze out of any map, whatever its topology.
+    const GENERATORS: [&dyn Generator; 8] = [
+        &Prims {},
+        &Backtracking {},
+        &Kruskal {},
+        &AldousBroder {},
+        &Wilson {},
+        &AldousBroderWilson { switch_at: 0.3 },
+        &GrowingTree {
+            selection: Selection::Random,
+        },
+        &HuntAndKill {},
+    ];
+
+    /// A map of every topology, with (rows, columns) that exercise its edge cases.
+    const LAYOUTS: [(Topology, (usize, usize)); 14] = [
+        (Topology::Square, (9, 7)),
+        (Topology::Hex, (8, 9)),
+        (Topology::Polar, (6, 32)),
+        // Rings that are only two cells wide border each neighbor on both sides
+        (Topology::Polar, (3, 2)),
+        (Topology::Triangle, (6, 11)),
+        (Topology::Levels(3), (12, 5)),
+        (Topology::Wrapped(Wrap::Horizontal), (6, 7)),
+        (Topology::Wrapped(Wrap::Vertical), (6, 7)),
+        (Topology::Wrapped(Wrap::Both), (6, 7)),
+        (Topology::Wrapped(Wrap::Mobius), (6, 7)),
+        (Topology::Wrapped(Wrap::Klein), (6, 7)),
+        (Topology::Cube, (18, 3)),
+        (Topology::Upsilon, (6, 7)),
+        (Topology::Sigma, (6, 7)),
+    ];
+
+    /// Asserts that `map` is a perfect maze, i.e. every cell was visited and
+    /// the open passages form a spanning tree.
+    fn assert_perfect(map: &Map) {
+        let mut passages = 0;
+        for (i, j) in map.get_all_grid_indices() {
+            assert!(map.get_cell(i, j).visited);
+            passages += map.get_open_neighbors(i, j).len();
+        }
+        // Every passage is counted twice, once from each end
+        assert_eq!(passages, 2 * (map.get_cell_count() - 1));
+
+        // Every cell should be reachable from the first one
+        let cells = map.get_all_grid_indices();
+        for cell in cells.iter().skip(1) {
+            assert!(breadth_first(map, cells[0], *cell).is_ok());
+        }
+    }
+
+    /// Builds a maze with each of the `generators` on an empty map of each of the
+    /// `layouts`, after passing the map to `prepare`, and asserts that every maze is
+    /// perfect. Each layout has a seed of its own, so failures can be reproduced.
+    fn assert_generates_perfect(
+        generators: &[&dyn Generator],
+        layouts: &[(Topology, (usize, usize))],
+        prepare: impl Fn(&mut Map),
+    ) {
+        for generator in generators.iter() {
+            for (seed, (topology, dimensions)) in layouts.iter().enumerate() {
+                let mut map = Map::with_topology(*dimensions, *topology);
+                prepare(&mut map);
+                map.build_maze(*generator, &mut ChaCha8Rng::seed_from_u64(seed as u64));
+
+                assert_perfect(&map);
+            }
+        }
+    }
+
+    #[test]
+    fn test_topologies() {
+        assert_generates_perfect(&GENERATORS, &LAYOUTS, |_| ());
+    }
+
+    #[test]
+    fn test_kruskal() {
+        // A single cell has no walls to remove, but is still part of the maze
+        assert_generates_perfect(&[&Kruskal {}], &[(Topology::Square, (1, 1))], |_| ());
+    }
+
+    #[test]
+    fn test_backtracking() {
+        // Starting in the middle of a corridor requires backtracking to the first cell
+        assert_generates_perfect(&[&Backtracking {}], &[(Topology::Square, (1, 3))], |map| {
+            map.set_start(Some((0, 1)))
+        });
+    }
+
+    #[test]
+    fn test_growing_tree() {
+        let selections = vec![
+            Selection::Newest,
+            Selection::Oldest,
+            Selection::Random,
+            Selection::Weighted(vec![(Selection::Newest, 0.75), (Selection::Random, 0.25)]),
+        ];
+
+        for selection in selections {
+            assert_generates_perfect(
+                &[&GrowingTree { selection }],
+                &[(Topology::Square, (10, 8)), (Topology::Hex, (7, 6))],
+                |_| (),
+            );
+        }
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        // These generators carve by grid index, which only works on square and hex maps
+        assert_generates_perfect(
+            &[&Eller {}, &BinaryTree {}, &Sidewinder {}],
+            &[(Topology::Square, (11, 7)), (Topology::Hex, (8, 10))],
+            |_| (),
+        );
+        assert_generates_perfect(
+            &[&RecursiveDivision {}],
+            &[(Topology::Square, (12, 9))],
+            |_| (),
+        );
+
+        // The top row of a binary tree or sidewinder maze is always a single corridor
+        for generator in [&BinaryTree {} as &dyn Generator, &Sidewinder {}].iter() {
+            let mut map = Map::empty((8, 10));
+            map.build_maze(*generator, &mut ChaCha8Rng::seed_from_u64(1));
+            assert!((0..9).all(|j| map.get_cell(0, j).is_open(Direction::East)));
+        }
+    }
+
+    #[test]
+    fn test_eller_rows() {
+        let bounded: Vec<Vec<Cell>> =
+            EllerRows::from_rng_with_height(6, 10, ChaCha8Rng::seed_from_u64(5)).collect();
+        assert_eq!(bounded.len(), 10);
+
+        // A stream with a fixed height ends with the same row as `finish` would produce
+        let mut endless = EllerRows::from_rng(6, ChaCha8Rng::seed_from_u64(5));
+        let mut rows: Vec<Vec<Cell>> = endless.by_ref().take(9).collect();
+        rows.push(endless.finish());
+        assert_eq!(format!("{:?}", bounded), format!("{:?}", rows));
+
+        // The rows make up the same (perfect) maze as the generator builds on a map
+        let mut map = Map::empty((10, 6));
+        map.build_maze(&Eller {}, &mut ChaCha8Rng::seed_from_u64(5));
+        assert_perfect(&map);
+        assert_eq!(
+            format!("{:?}", map.get_terrain()),
+            format!("{:?}", bounded.concat())
+        );
+    }
+
+    #[test]
+    fn test_eller_rows_from_rng() {
+        let a: Vec<Vec<Cell>> = EllerRows::from_rng(5, ChaCha8Rng::seed_from_u64(99))
+            .take(20)
+            .collect();
+        let b: Vec<Vec<Cell>> = EllerRows::from_rng(5, ChaCha8Rng::seed_from_u64(99))
+            .take(20)
+            .collect();
+
+        assert_eq!(format!("{:?}", a), format!("{:?}", b));
+    }
+
+    #[test]
+    fn test_seeded_generators() {
+        let generators: Vec<Box<dyn Generator>> = vec![
+            Box::new(Prims {}),
+            Box::new(Backtracking {}),
+            Box::new(Kruskal {}),
+            Box::new(AldousBroder {}),
+            Box::new(Wilson {}),
+            Box::new(AldousBroderWilson { switch_at: 0.5 }),
+            Box::new(Eller {}),
+            Box::new(RecursiveDivision {}),
+            Box::new(GrowingTree {
+                selection: Selection::Weighted(vec![
+                    (Selection::Newest, 0.5),
+                    (Selection::Oldest, 0.5),
+                ]),
+            }),
+            Box::new(HuntAndKill {}),
+            Box::new(BinaryTree {}),
+            Box::new(Sidewinder {}),
+        ];
+
+        for generator in generators.iter() {
+            let mut a = Map::empty((9, 9));
+            generator.build(&mut a, &mut ChaCha8Rng::seed_from_u64(7));
+
+            let mut b = Map::empty((9, 9));
+            generator.build(&mut b, &mut ChaCha8Rng::seed_from_u64(7));
+
+            assert_eq!(format!("{:?}", a), format!("{:?}", b));
+        }
+    }
+
+    #[test]
+    fn test_masked() {
+        let template = "XX..XX\n.X..X.\n......\n.X..X.\nXX..XX";
+        assert_generates_perfect(&GENERATORS, &[(Topology::Square, (5, 6))], |map| {
+            map.set_mask(Some(Mask::from_ascii(template)))
+        });
+
+        // Nothing is carved outside of the mask
+        let mut map = Map::empty((5, 6));
+        map.set_mask(Some(Mask::from_ascii(template)));
+        map.build_maze(&Prims {}, &mut ChaCha8Rng::seed_from_u64(0));
+        assert!(!map.get_cell(0, 0).visited);
+        assert_eq!(map.get_neighbors(1, 0), vec![(2, 0)]);
+    }
+
+    #[test]
+    fn test_weave() {
+        let generators = [&Backtracking {} as &dyn Generator, &Kruskal {}, &Prims {}];
+        assert_generates_perfect(&generators, &[(Topology::Square, (12, 12))], |map| {
+            map.set_weave(true)
+        });
+
+        // Kruskal's algorithm always places some crossings on a map this size
+        let mut map = Map::empty((12, 12));
+        map.set_weave(true);
+        map.build_maze(&Kruskal {}, &mut ChaCha8Rng::seed_from_u64(3));
+        assert!(map.get_terrain().iter().any(|cell| cell.has_tunnel()));
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        assert_eq!(Selection::Newest.choose(5, &mut rng), 4);
+        assert_eq!(Selection::Oldest.choose(5, &mut rng), 0);
+        assert!(Selection::Random.choose(5, &mut rng) < 5);
+
+        let newest_only =
+            Selection::Weighted(vec![(Selection::Newest, 1.0), (Selection::Oldest, 0.0)]);
+        for _ in 0..100 {
+            assert_eq!(newest_only.choose(5, &mut rng), 4);
+        }
+    }
+
+    #[test]
+    fn test_steps_replay() {
+        let generators: Vec<Box<dyn Generator>> = vec![
+            Box::new(Prims {}),
+            Box::new(Backtracking {}),
+            Box::new(Kruskal {}),
+            Box::new(AldousBroderWilson { switch_at: 0.5 }),
+            Box::new(Eller {}),
+            Box::new(RecursiveDivision {}),
+            Box::new(GrowingTree {
+                selection: Selection::Oldest,
+            }),
+            Box::new(HuntAndKill {}),
+            Box::new(BinaryTree {}),
+            Box::new(Sidewinder {}),
+        ];
+
+        for generator in generators.iter() {
+            let mut rng = ChaCha8Rng::seed_from_u64(0);
+            let mut map = Map::empty((7, 9));
+            let events: Vec<GenEvent> = generator.steps(&mut map, &mut rng).collect();
+
+            // Replaying the events onto an empty map should reproduce the maze
+            let mut replayed = Map::empty((7, 9));
+            for event in events {
+                match event {
+                    GenEvent::Visit(cell) => replayed.visit(cell.0, cell.1),
+                    GenEvent::Carve(to, from) => replayed.open_path_between(to, from),
+                    GenEvent::Wall(to, from) => replayed.close_path_between(to, from),
+                    GenEvent::Backtrack(_) => (),
+                }
+            }
+
+            assert_eq!(format!("{:?}", map), format!("{:?}", replayed));
+        }
+    }
+
+    #[test]
+    fn test_steps_pause() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut map = Map::empty((5, 5));
+        let mut steps = Backtracking {}.steps(&mut map, &mut rng);
+
+        // The first event always visits the start cell
+        let first = steps.next();
+        let visited = steps
+            .get_map()
+            .get_terrain()
+            .iter()
+            .filter(|cell| cell.visited)
+            .count();
+        assert!(matches!(first, Some(GenEvent::Visit(_))));
+        assert_eq!(visited, 1);
+
+        steps.for_each(drop);
+        assert!(map.get_terrain().iter().all(|cell| cell.visited));
     }
 }
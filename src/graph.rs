@@ -0,0 +1,329 @@
+use rand::{Rng, RngCore};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+// Reference: https://en.wikipedia.org/wiki/Maze_generation_algorithm#Graph_theory
+
+/// Anything that a maze can be carved out of: a set of nodes, where each node can
+/// be joined to some of the others by a passage. The generators and the search
+/// functions only ever see a map through this trait, so they work just as well
+/// over a region graph or a road network as they do over a grid of cells.
+///
+/// The graph needn't be connected, but a maze can't bridge the gaps between its
+/// pieces: most generators only carve the piece that contains their start node,
+/// and leave every other node unvisited (`Kruskal` carves each piece separately).
+pub trait Graph {
+    /// Identifies a single node (such as the row and column of a cell).
+    type Node: Copy + Debug + Eq + Hash + Ord + 'static;
+
+    /// Returns every node that is part of the maze.
+    fn get_nodes(&self) -> Vec<Self::Node>;
+
+    /// Returns the number of nodes that are part of the maze.
+    fn get_node_count(&self) -> usize {
+        self.get_nodes().len()
+    }
+
+    /// Returns `true` if `node` is part of the maze.
+    fn contains(&self, node: Self::Node) -> bool {
+        self.get_nodes().contains(&node)
+    }
+
+    /// Returns the nodes that a passage could be carved to from `node`.
+    fn get_neighbors(&self, node: Self::Node) -> Vec<Self::Node>;
+
+    /// Returns the nodes that can be reached from `node` through an open passage.
+    fn get_open_neighbors(&self, node: Self::Node) -> Vec<Self::Node>;
+
+    /// Returns the neighbors of `node` that are still separated from it by a wall.
+    fn get_closed_neighbors(&self, node: Self::Node) -> Vec<Self::Node> {
+        let open = self.get_open_neighbors(node);
+
+        self.get_neighbors(node)
+            .into_iter()
+            .filter(|neighbor| !open.contains(neighbor))
+            .collect()
+    }
+
+    /// Returns the nodes that a passage could be carved to from `node` that are
+    /// already part of the maze.
+    fn get_visited_neighbors(&self, node: Self::Node) -> Vec<Self::Node> {
+        self.get_neighbors(node)
+            .into_iter()
+            .filter(|neighbor| self.is_visited(*neighbor))
+            .collect()
+    }
+
+    /// Returns the nodes that a passage could be carved to from `node` that aren't
+    /// part of the maze yet.
+    fn get_unvisited_neighbors(&self, node: Self::Node) -> Vec<Self::Node> {
+        self.get_neighbors(node)
+            .into_iter()
+            .filter(|neighbor| !self.is_visited(*neighbor))
+            .collect()
+    }
+
+    /// Returns the pairs of nodes on opposite sides of `node` that a corridor and a
+    /// tunnel could join, if the maze allows passages to cross over one another.
+    fn get_crossings(&self, _node: Self::Node) -> Vec<[Self::Node; 2]> {
+        vec![]
+    }
+
+    /// Returns the cost of travelling from `from` to `to`, through the passage
+    /// between them. Every passage costs the same by default.
+    fn get_cost(&self, _from: Self::Node, _to: Self::Node) -> usize {
+        1
+    }
+
+    /// Returns an estimate of the cost of travelling from `from` to `to`, which
+    /// guides A* search towards the goal. The estimate must never be larger than
+    /// the actual cost, otherwise A* may find a longer path than necessary, so by
+    /// default no estimate is made at all.
+    fn estimate_cost(&self, _from: Self::Node, _to: Self::Node) -> usize {
+        0
+    }
+
+    /// Returns `true` if `node` has been made part of the maze.
+    fn is_visited(&self, node: Self::Node) -> bool;
+
+    /// Makes `node` part of the maze.
+    fn visit(&mut self, node: Self::Node);
+
+    /// Carves a passage between `to` and `from`.
+    fn open_path_between(&mut self, to: Self::Node, from: Self::Node);
+
+    /// Returns the node that generators should start carving from.
+    fn get_start_node(&self, rng: &mut dyn RngCore) -> Self::Node;
+}
+
+/// A maze over an arbitrary graph, whose nodes are numbered from zero and whose
+/// edges are the places where a passage may be carved. This is the simplest way
+/// to build a maze over a custom topology: describe which nodes border each other,
+/// then hand it to any of the generators that aren't tied to a grid.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphMaze {
+    // The nodes that each node borders
+    neighbors: Vec<Vec<usize>>,
+
+    // The nodes that each node is joined to by an open passage
+    passages: Vec<Vec<usize>>,
+
+    // Whether or not each node is part of the maze
+    visited: Vec<bool>,
+
+    // The node to start carving from (or `None` to pick one at random)
+    start: Option<usize>,
+}
+
+impl GraphMaze {
+    /// Constructs a new graph with `count` nodes and no edges.
+    pub fn new(count: usize) -> GraphMaze {
+        GraphMaze {
+            neighbors: vec![vec![]; count],
+            passages: vec![vec![]; count],
+            visited: vec![false; count],
+            start: None,
+        }
+    }
+
+    /// Constructs a new graph with `count` nodes, where each pair in `edges` is a
+    /// pair of nodes that border each other.
+    pub fn from_edges(count: usize, edges: impl IntoIterator<Item = (usize, usize)>) -> GraphMaze {
+        let mut graph = GraphMaze::new(count);
+        for (a, b) in edges {
+            graph.add_edge(a, b);
+        }
+
+        graph
+    }
+
+    /// Records that nodes `a` and `b` border each other, so that a passage may be
+    /// carved between them. Adding the same edge twice has no effect.
+    pub fn add_edge(&mut self, a: usize, b: usize) {
+        assert!(
+            a < self.neighbors.len() && b < self.neighbors.len(),
+            "Both ends of an edge must be nodes of the graph"
+        );
+        assert_ne!(a, b, "A node can't border itself");
+
+        if !self.neighbors[a].contains(&b) {
+            self.neighbors[a].push(b);
+            self.neighbors[b].push(a);
+        }
+    }
+
+    /// Returns every pair of nodes that border each other, with the smaller node first.
+    pub fn get_edges(&self) -> Vec<(usize, usize)> {
+        self.neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(a, neighbors)| {
+                neighbors
+                    .iter()
+                    .filter(move |b| a < **b)
+                    .map(move |b| (a, *b))
+            })
+            .collect()
+    }
+
+    /// Returns `true` if there is an open passage between nodes `a` and `b`.
+    pub fn is_open(&self, a: usize, b: usize) -> bool {
+        self.passages[a].contains(&b)
+    }
+
+    /// Sets the node that generators should start carving from.
+    pub fn set_start(&mut self, start: Option<usize>) {
+        if let Some(start) = start {
+            assert!(
+                start < self.neighbors.len(),
+                "The start must be a node of the graph"
+            );
+        }
+        self.start = start;
+    }
+}
+
+impl Graph for GraphMaze {
+    type Node = usize;
+
+    fn get_nodes(&self) -> Vec<usize> {
+        (0..self.neighbors.len()).collect()
+    }
+
+    fn get_node_count(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    fn contains(&self, node: usize) -> bool {
+        node < self.neighbors.len()
+    }
+
+    fn get_neighbors(&self, node: usize) -> Vec<usize> {
+        self.neighbors[node].clone()
+    }
+
+    fn get_open_neighbors(&self, node: usize) -> Vec<usize> {
+        self.passages[node].clone()
+    }
+
+    fn is_visited(&self, node: usize) -> bool {
+        self.visited[node]
+    }
+
+    fn visit(&mut self, node: usize) {
+        self.visited[node] = true;
+    }
+
+    fn open_path_between(&mut self, to: usize, from: usize) {
+        assert!(
+            self.neighbors[to].contains(&from),
+            "Passages can only be carved between nodes that border each other"
+        );
+
+        if !self.is_open(to, from) {
+            self.passages[to].push(from);
+            self.passages[from].push(to);
+        }
+    }
+
+    fn get_start_node(&self, rng: &mut dyn RngCore) -> usize {
+        match self.start {
+            Some(start) => start,
+            None => rng.gen_range(0, self.neighbors.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{
+        AldousBroder, AldousBroderWilson, Backtracking, Generator, Kruskal, Prims, Wilson,
+    };
+    use crate::search::breadth_first;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_edges() {
+        let mut graph = GraphMaze::from_edges(4, vec![(0, 1), (1, 2), (2, 0), (1, 0)]);
+        graph.add_edge(2, 3);
+
+        assert_eq!(graph.get_edges(), vec![(0, 1), (0, 2), (1, 2), (2, 3)]);
+        assert_eq!(graph.get_neighbors(2), vec![1, 0, 3]);
+
+        graph.open_path_between(3, 2);
+        assert!(graph.is_open(2, 3));
+        assert_eq!(graph.get_closed_neighbors(2), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_generators() {
+        // A wheel: a ring of eight nodes, each of which also borders the hub
+        let ring = (0..8).map(|k| (k, (k + 1) % 8));
+        let spokes = (0..8).map(|k| (k, 8));
+        let edges: Vec<_> = ring.chain(spokes).collect();
+
+        let generators: Vec<Box<dyn Generator<GraphMaze>>> = vec![
+            Box::new(Prims {}),
+            Box::new(Backtracking {}),
+            Box::new(Kruskal {}),
+            Box::new(Wilson {}),
+        ];
+
+        for generator in generators.iter() {
+            let mut graph = GraphMaze::from_edges(9, edges.clone());
+            generator.build(&mut graph, &mut ChaCha8Rng::seed_from_u64(0));
+
+            // Every node is reachable, along a spanning tree of the graph
+            let passages: usize = (0..9).map(|k| graph.get_open_neighbors(k).len()).sum();
+            assert_eq!(passages, 2 * 8);
+            assert!((0..9).all(|k| graph.is_visited(k)));
+            for k in 1..9 {
+                assert_eq!(breadth_first(&graph, k, 0).unwrap()[0], 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_disconnected() {
+        // A pair of nodes, a triangle and a node on its own
+        let edges = vec![(0, 1), (2, 3), (3, 4), (4, 2)];
+
+        let generators: Vec<Box<dyn Generator<GraphMaze>>> = vec![
+            Box::new(Prims {}),
+            Box::new(Backtracking {}),
+            Box::new(Wilson {}),
+            Box::new(AldousBroder {}),
+            Box::new(AldousBroderWilson { switch_at: 0.9 }),
+        ];
+
+        for generator in generators.iter() {
+            for start in 0..6 {
+                let mut graph = GraphMaze::from_edges(6, edges.clone());
+                graph.set_start(Some(start));
+                generator.build(&mut graph, &mut ChaCha8Rng::seed_from_u64(start as u64));
+
+                // Only the piece that contains the start is carved
+                let piece = match start {
+                    0 | 1 => vec![0, 1],
+                    2..=4 => vec![2, 3, 4],
+                    _ => vec![5],
+                };
+                for k in 0..6 {
+                    assert_eq!(graph.is_visited(k), piece.contains(&k));
+                }
+                let passages: usize = piece
+                    .iter()
+                    .map(|k| graph.get_open_neighbors(*k).len())
+                    .sum();
+                assert_eq!(passages, 2 * (piece.len() - 1));
+            }
+        }
+
+        // Kruskal joins up each piece on its own
+        let mut graph = GraphMaze::from_edges(6, edges);
+        Kruskal {}.build(&mut graph, &mut ChaCha8Rng::seed_from_u64(0));
+        assert!((0..6).all(|k| graph.is_visited(k)));
+    }
+}
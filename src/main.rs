@@ -1,12 +1,42 @@
 #![allow(dead_code)]
 
+mod braid;
+mod builder;
+mod disjoint_set;
+mod distance;
 mod generators;
+mod graph;
 mod map;
+mod mask;
+mod mesh;
 mod search;
+mod svg;
+mod topology;
+mod voronoi;
 
-use crate::search::breadth_first;
+use generators::{GenEvent, Generator};
 use map::Map;
 use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
+
+/// Prints each frame of `generator` building a maze with the specified
+/// `dimensions` to the terminal, redrawing in place after every passage.
+fn animate(generator: &dyn Generator, dimensions: (usize, usize)) {
+    let mut rng = rand::thread_rng();
+    let mut map = Map::empty(dimensions);
+    let mut steps = generator.steps(&mut map, &mut rng);
+
+    while let Some(event) = steps.next() {
+        if let GenEvent::Carve(..) | GenEvent::Wall(..) = event {
+            // Move up cursor
+            println!("\x1b[{}F", dimensions.0 * 3 + 2);
+            println!("{:?}", steps.get_map());
+
+            sleep(Duration::from_millis(100));
+        }
+    }
+}
 
 // See: https://www.joshmcguigan.com/blog/custom-exit-status-codes-rust/
 fn main() -> std::io::Result<()> {
@@ -14,8 +44,10 @@ fn main() -> std::io::Result<()> {
     map.save_ascii(Path::new("maze.txt"))?;
     println!("{:?}", map);
 
-    //let path = breadth_first(&map, (0, 0), (29, 29));
-    //println!("{:?}", path);
+    //animate(&generators::Backtracking {}, (10, 10));
+
+    //let path = search::breadth_first(&map, (0, 0), (29, 29)).unwrap();
+    //search::print_path(&path);
 
     Ok(())
 }
@@ -1,7 +1,14 @@
+use crate::distance;
 use crate::generators::{Generator, Prims};
-use rand::Rng;
+use crate::graph::Graph;
+use crate::mask::Mask;
+use crate::mesh;
+use crate::svg;
+use crate::topology::{Direction, Topology};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
 // References:
@@ -16,66 +23,155 @@ pub struct Cell {
     // Whether or not this cell has already been processed ("visited")
     pub(crate) visited: bool,
 
-    // Whether or not we can travel north from this cell
-    pub n: bool,
+    // The directions in which we can travel from this cell (one bit per `Direction`)
+    links: u16,
 
-    // Whether or not we can travel south from this cell
-    pub s: bool,
-
-    // Whether or not we can travel east from this cell
-    pub e: bool,
-
-    // Whether or not we can travel west from this cell
-    pub w: bool,
+    // Whether or not a passage runs underneath this cell, at right angles to the
+    // cell's own passages (only in weave mazes)
+    tunnel: bool,
 }
 
 impl Cell {
     pub fn new() -> Cell {
         Cell {
             visited: false,
-            n: false,
-            s: false,
-            e: false,
-            w: false,
+            links: 0,
+            tunnel: false,
         }
     }
 
-    /// Returns `true` if all passages leading to this cell are "open," and
-    /// `false` otherwise.
-    pub fn is_completely_open(&self) -> bool {
-        self.n && self.s && self.e && self.w
+    /// Returns `true` if a passage runs underneath this cell, and `false` otherwise.
+    pub fn has_tunnel(&self) -> bool {
+        self.tunnel
+    }
+
+    /// Returns `true` if we can travel from this cell in the specified `direction`,
+    /// and `false` otherwise.
+    pub fn is_open(&self, direction: Direction) -> bool {
+        self.links & direction.bit() != 0
+    }
+
+    /// Sets whether or not we can travel from this cell in the specified `direction`.
+    pub(crate) fn set_open(&mut self, direction: Direction, open: bool) {
+        if open {
+            self.links |= direction.bit();
+        } else {
+            self.links &= !direction.bit();
+        }
     }
 }
 
 /// A struct representing a game map that is filled from edge-to-edge by a
 /// 2-dimensional maze.
 pub struct Map {
-    // The dimensions of the map (width, height)
+    // The dimensions of the map (rows, columns)
     dimensions: (usize, usize),
 
+    // The shape of the cells and how they are connected to one another
+    topology: Topology,
+
     // The actual map data (a 1D-array of cells, interpreted as a 2D-array)
     terrain: Vec<Cell>,
+
+    // The cell that generators should start carving from (if `None`, a random
+    // cell is chosen instead)
+    start: Option<(usize, usize)>,
+
+    // The cells through which the maze is entered and left, along with the side
+    // of each that is open to the outside of the map
+    entrance: Option<((usize, usize), Direction)>,
+    exit: Option<((usize, usize), Direction)>,
+
+    // The shape that the maze is restricted to (if `None`, the maze fills the
+    // entire map)
+    mask: Option<Mask>,
+
+    // Whether or not passages are allowed to tunnel underneath other passages
+    weave: bool,
 }
 
 impl Map {
     /// Constructs and populates a new map.
     pub fn new(dimensions: (usize, usize)) -> Map {
-        let mut map = Map {
-            dimensions,
-            terrain: vec![Cell::new(); dimensions.0 * dimensions.1],
-        };
+        Map::from_rng(dimensions, &mut rand::thread_rng())
+    }
+
+    /// Constructs and populates a new map, deterministically: the same `seed`
+    /// and `dimensions` will always produce the same maze, on every platform
+    /// (the seed drives a ChaCha generator, whose output is fixed, unlike that of
+    /// `StdRng`, which may change between versions of `rand`).
+    pub fn from_seed(dimensions: (usize, usize), seed: u64) -> Map {
+        Map::from_rng(dimensions, &mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Constructs and populates a new map, drawing all random numbers from `rng`.
+    pub fn from_rng(dimensions: (usize, usize), rng: &mut dyn RngCore) -> Map {
+        let mut map = Map::empty(dimensions);
 
         let generator = Prims {};
 
-        map.build_maze(generator);
+        map.build_maze(&generator, rng);
         map
     }
 
-    /// Returns the dimensions (width, height) of the map.
+    /// Constructs a new map where every cell is still a wall, i.e. no maze has
+    /// been generated yet. Use `build_maze` (or a `MapBuilder`) to populate it.
+    pub fn empty(dimensions: (usize, usize)) -> Map {
+        Map::with_topology(dimensions, Topology::Square)
+    }
+
+    /// Constructs a new, empty map whose cells are shaped and connected according
+    /// to the specified `topology`. A multi-level map must have at least one row
+    /// per level, and the same number of rows on every level.
+    pub fn with_topology(dimensions: (usize, usize), topology: Topology) -> Map {
+        if let Topology::Levels(levels) = topology {
+            if levels == 0 || dimensions.0 < levels || dimensions.0 % levels != 0 {
+                panic!(
+                    "Attempting to split {} rows into {} levels of equal height",
+                    dimensions.0, levels
+                );
+            }
+        }
+
+        Map {
+            dimensions,
+            topology,
+            terrain: vec![Cell::new(); dimensions.0 * dimensions.1],
+            start: None,
+            entrance: None,
+            exit: None,
+            mask: None,
+            weave: false,
+        }
+    }
+
+    /// Constructs a new, empty map with the specified number of `levels`, each of
+    /// which is a grid of square cells with the specified `dimensions`. Cells on
+    /// neighboring levels are connected by stairs.
+    pub fn with_levels(dimensions: (usize, usize), levels: usize) -> Map {
+        Map::with_topology(
+            (dimensions.0 * levels, dimensions.1),
+            Topology::Levels(levels),
+        )
+    }
+
+    /// Constructs a new, empty map that covers the six faces of a cube, each of which
+    /// is a grid of `size` by `size` square cells. The faces are stored one after
+    /// the other, so the map is `6 * size` cells tall.
+    pub fn with_cube(size: usize) -> Map {
+        Map::with_topology((6 * size, size), Topology::Cube)
+    }
+
+    /// Returns the dimensions (rows, columns) of the map.
     pub fn get_dimensions(&self) -> (usize, usize) {
         self.dimensions
     }
 
+    /// Returns the topology of the map.
+    pub fn get_topology(&self) -> Topology {
+        self.topology
+    }
+
     /// Returns an immutable reference to the map's terrain, which is a 1D
     /// vector of `Cell` structs.
     pub fn get_terrain(&self) -> &Vec<Cell> {
@@ -90,50 +186,181 @@ impl Map {
         let header = vec![0xEF, 0xBB, 0xBF];
         let bom = std::str::from_utf8(&header).unwrap();
 
-        file.write_all(&format!("{}{:?}", bom, self).as_bytes())?;
+        file.write_all(format!("{}{:?}", bom, self).as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Saves an SVG drawing of the maze to `path`, where each cell is roughly
+    /// `cell_size` units across. Unlike `save_ascii`, this supports every topology.
+    pub fn save_svg(&self, path: &Path, cell_size: f32) -> std::io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(svg::render(self, cell_size).as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Saves a 3D model of a cube maze to `path` as a Wavefront OBJ file, where each
+    /// cell is `cell_size` units across (see `mesh::render`).
+    pub fn save_obj(&self, path: &Path, cell_size: f32) -> std::io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(mesh::render(self, cell_size).as_bytes())?;
 
         Ok(())
     }
 
     /// Builds a maze using the specified `generator`.
-    fn build_maze(&mut self, generator: impl Generator) {
-        generator.build(self);
+    pub fn build_maze(&mut self, generator: &dyn Generator, rng: &mut dyn RngCore) {
+        generator.build(self, rng);
+    }
+
+    /// Returns `true` if passages are allowed to tunnel underneath other passages,
+    /// and `false` otherwise.
+    pub fn is_weave(&self) -> bool {
+        self.weave
+    }
+
+    /// Sets whether or not passages are allowed to tunnel underneath other passages,
+    /// producing a "weave" maze. A passage can only tunnel underneath a single cell
+    /// that is itself a straight corridor running at right angles to the tunnel.
+    /// Only square maps support weaving.
+    pub fn set_weave(&mut self, weave: bool) {
+        if weave && self.topology != Topology::Square {
+            panic!("Attempting to weave a map that doesn't have square cells");
+        }
+
+        self.weave = weave;
     }
 
     /// Opens a path between cells `to` and `from`. For example, if `to` is
-    /// above `from` on the map, then `to`'s "south" flag will be set to `true`,
+    /// above `from` on a square map, then `to`'s "south" flag will be set to `true`,
     /// meaning that the user can travel south from `to` down to `from` and vice-versa.
-    pub(crate) fn open_path_between(&mut self, to: (usize, usize), from: (usize, usize)) {
-        if !self.get_neighbors(to.0, to.1).contains(&from) {
-            panic!("Attempting to open a path between non-adjacent cells");
+    ///
+    /// In weave mazes, `to` and `from` can also be two cells apart, in which case
+    /// the path tunnels underneath the cell between them.
+    pub fn open_path_between(&mut self, to: (usize, usize), from: (usize, usize)) {
+        if self.get_neighbors(to.0, to.1).contains(&from) {
+            self.set_path_between(to, from, true);
+            return;
         }
 
-        if to.0 < from.0 {
-            // `to` is above `from`: we can move down from `to` and up from `from`
-            self.get_cell_mut(to.0, to.1).s = true;
-            self.get_cell_mut(from.0, from.1).n = true;
+        match self.get_tunnel_between(to, from) {
+            Some((under, direction)) if self.can_tunnel_under(under, direction) => {
+                self.set_tunnel_between(to, from, under, direction, true)
+            }
+            _ => panic!("Attempting to open a path between non-adjacent cells"),
         }
-        if to.0 > from.0 {
-            // `to` is below `from`: we can move up from `to` and down from `from`
-            self.get_cell_mut(to.0, to.1).n = true;
-            self.get_cell_mut(from.0, from.1).s = true;
+    }
+
+    /// Closes the path between cells `to` and `from`, i.e. the inverse of
+    /// `open_path_between`: afterwards, the user can no longer travel from `to`
+    /// to `from` or vice-versa.
+    pub fn close_path_between(&mut self, to: (usize, usize), from: (usize, usize)) {
+        if self.get_neighbors(to.0, to.1).contains(&from) {
+            self.set_path_between(to, from, false);
+            return;
         }
-        if to.1 < from.1 {
-            // `to` is left from `from`: we can move right from `to` and left from `from`
-            self.get_cell_mut(to.0, to.1).e = true;
-            self.get_cell_mut(from.0, from.1).w = true;
+
+        match self.get_tunnel_between(to, from) {
+            Some((under, direction)) if self.get_cell(under.0, under.1).tunnel => {
+                self.set_tunnel_between(to, from, under, direction, false)
+            }
+            _ => panic!("Attempting to close a path between non-adjacent cells"),
         }
-        if to.1 > from.1 {
-            // `to` is right from `from`: we can move left from `to` and right from `from`
-            self.get_cell_mut(to.0, to.1).w = true;
-            self.get_cell_mut(from.0, from.1).e = true;
+    }
+
+    /// If cells `to` and `from` are in the same row or column, with a single cell
+    /// between them, returns that cell and the direction from `to` towards it.
+    fn get_tunnel_between(
+        &self,
+        to: (usize, usize),
+        from: (usize, usize),
+    ) -> Option<((usize, usize), Direction)> {
+        if !self.weave {
+            return None;
+        }
+
+        [
+            Direction::North,
+            Direction::South,
+            Direction::West,
+            Direction::East,
+        ]
+        .iter()
+        .find_map(|direction| {
+            let under = self.get_neighbor(to.0, to.1, *direction)?;
+            match self.get_neighbor(under.0, under.1, *direction) {
+                Some(beyond) if beyond == from => Some((under, *direction)),
+                _ => None,
+            }
+        })
+    }
+
+    /// Returns `true` if a passage heading in the specified `direction` could tunnel
+    /// underneath cell `under`, i.e. if `under` is part of the maze and its only
+    /// passages form a straight corridor at right angles to `direction`.
+    fn can_tunnel_under(&self, under: (usize, usize), direction: Direction) -> bool {
+        let cell = self.get_cell(under.0, under.1);
+        let across = match direction {
+            Direction::North | Direction::South => [Direction::West, Direction::East],
+            _ => [Direction::North, Direction::South],
+        };
+        let corridor = across[0].bit() | across[1].bit();
+
+        self.weave && cell.visited && !cell.tunnel && cell.links == corridor
+    }
+
+    /// Sets the flags that control whether the user can travel between cells `to`
+    /// and `from` by tunneling underneath cell `under` (which lies in the specified
+    /// `direction` from `to`).
+    fn set_tunnel_between(
+        &mut self,
+        to: (usize, usize),
+        from: (usize, usize),
+        under: (usize, usize),
+        direction: Direction,
+        open: bool,
+    ) {
+        let backward = direction.get_opposite().unwrap();
+
+        self.get_cell_mut(to.0, to.1).set_open(direction, open);
+        self.get_cell_mut(from.0, from.1).set_open(backward, open);
+        self.get_cell_mut(under.0, under.1).tunnel = open;
+    }
+
+    /// Visits every cell and opens the paths between all adjacent cells, so that
+    /// the entire map becomes a single open room. This is the starting point for
+    /// generators that add walls rather than carve passages.
+    pub fn open_all_paths(&mut self) {
+        for current in self.get_all_grid_indices() {
+            self.visit(current.0, current.1);
+
+            for neighbor in self.get_neighbors(current.0, current.1) {
+                self.set_path_between(current, neighbor, true);
+            }
+        }
+    }
+
+    /// Sets the flags that control whether the user can travel between the two
+    /// adjacent cells `to` and `from` to `open`.
+    fn set_path_between(&mut self, to: (usize, usize), from: (usize, usize), open: bool) {
+        let forward = self
+            .topology
+            .get_direction_between(self.dimensions, to, from);
+        let backward = self
+            .topology
+            .get_direction_between(self.dimensions, from, to);
+
+        if let (Some(forward), Some(backward)) = (forward, backward) {
+            self.get_cell_mut(to.0, to.1).set_open(forward, open);
+            self.get_cell_mut(from.0, from.1).set_open(backward, open);
         }
     }
 
     /// Given a 1D index into this map's array of cells, returns the 2D
     /// grid index <`i`, `j`> corresponding to this cell's position in the
     /// terrain.
-    fn absolute_to_grid_indices(&self, idx: usize) -> (usize, usize) {
+    pub(crate) fn absolute_to_grid_indices(&self, idx: usize) -> (usize, usize) {
         // Row
         let i = idx / self.dimensions.1;
 
@@ -143,153 +370,605 @@ impl Map {
         (i, j)
     }
 
+    /// Given the 2D grid index <`i`, `j`> of a cell, returns the corresponding
+    /// 1D index into this map's array of cells (the inverse of
+    /// `absolute_to_grid_indices`).
+    pub(crate) fn grid_to_absolute_indices(&self, i: usize, j: usize) -> usize {
+        i * self.dimensions.1 + j
+    }
+
+    /// Returns the grid indices of every cell in the map, in row-major order.
+    pub fn get_all_grid_indices(&self) -> Vec<(usize, usize)> {
+        (0..self.terrain.len())
+            .map(|idx| self.absolute_to_grid_indices(idx))
+            .filter(|(i, j)| self.contains(*i, *j))
+            .collect()
+    }
+
+    /// Returns the number of cells in the map (on polar or masked maps, this is
+    /// less than the number of rows times the number of columns).
+    pub fn get_cell_count(&self) -> usize {
+        match self.mask {
+            Some(_) => self.get_all_grid_indices().len(),
+            None => (0..self.dimensions.0).map(|i| self.get_row_width(i)).sum(),
+        }
+    }
+
+    /// Returns the number of cells in row `i` of the map.
+    pub fn get_row_width(&self, i: usize) -> usize {
+        self.topology.get_row_width(self.dimensions, i)
+    }
+
+    /// Returns `true` if cell <`i`, `j`> is part of the map, and `false` otherwise.
+    pub fn contains(&self, i: usize, j: usize) -> bool {
+        let masked = match &self.mask {
+            Some(mask) => !mask.is_enabled(i, j),
+            None => false,
+        };
+
+        !masked && self.topology.contains(self.dimensions, i, j)
+    }
+
+    /// Returns the shape that the maze is restricted to, if one was set.
+    pub fn get_mask(&self) -> Option<&Mask> {
+        self.mask.as_ref()
+    }
+
+    /// Restricts the maze to the shape described by `mask`, which must have the same
+    /// dimensions as the map. Generators only carve inside of the shape, so it should
+    /// be set before the maze is built. The shape must also be a single connected
+    /// region, since a maze can't join up cells that don't border one another, and
+    /// it must include the start cell, if one was set.
+    pub fn set_mask(&mut self, mask: Option<Mask>) {
+        self.mask = mask;
+
+        if let Some(mask) = &self.mask {
+            if mask.get_dimensions() != self.dimensions {
+                panic!("Attempting to apply a mask with different dimensions");
+            }
+            if mask.get_count() == 0 {
+                panic!("Attempting to apply a mask that excludes every cell");
+            }
+            if !self.is_connected() {
+                panic!("Attempting to apply a mask that splits the map into separate regions");
+            }
+            if let Some((i, j)) = self.start {
+                if !mask.is_enabled(i, j) {
+                    panic!("Attempting to apply a mask that excludes the start cell");
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if every cell of the map can be reached from every other cell
+    /// by stepping between neighbors, regardless of which walls are open.
+    fn is_connected(&self) -> bool {
+        let cells = self.get_all_grid_indices();
+        let mut reached = vec![false; self.terrain.len()];
+        let mut frontier = vec![];
+        let mut count = 0;
+
+        if let Some(&(i, j)) = cells.first() {
+            reached[self.grid_to_absolute_indices(i, j)] = true;
+            frontier.push((i, j));
+        }
+        while let Some((i, j)) = frontier.pop() {
+            count += 1;
+
+            for (ni, nj) in self.get_neighbors(i, j) {
+                let idx = self.grid_to_absolute_indices(ni, nj);
+                if !reached[idx] {
+                    reached[idx] = true;
+                    frontier.push((ni, nj));
+                }
+            }
+        }
+
+        count == cells.len()
+    }
+
+    /// Returns the cell that generators should start carving from, if one was set.
+    pub fn get_start(&self) -> Option<(usize, usize)> {
+        self.start
+    }
+
+    /// Sets the cell that generators should start carving from. If `start` is `None`,
+    /// generators will choose a random cell instead.
+    pub fn set_start(&mut self, start: Option<(usize, usize)>) {
+        if let Some((i, j)) = start {
+            if !self.contains(i, j) {
+                panic!("Attempting to start outside of the map");
+            }
+        }
+
+        self.start = start;
+    }
+
+    /// Returns the cell through which the maze is entered, if one was set.
+    pub fn get_entrance(&self) -> Option<(usize, usize)> {
+        self.entrance.map(|(cell, _)| cell)
+    }
+
+    /// Sets the cell through which the maze is entered, opening the wall between it
+    /// and the outside of the map (and closing the wall of the previous entrance,
+    /// if there was one). The entrance must lie along the edge of the map.
+    pub fn set_entrance(&mut self, entrance: Option<(usize, usize)>) {
+        let opening = self.replace_opening(self.entrance, entrance, self.exit, false);
+        self.entrance = opening;
+    }
+
+    /// Returns the cell through which the maze is left, if one was set.
+    pub fn get_exit(&self) -> Option<(usize, usize)> {
+        self.exit.map(|(cell, _)| cell)
+    }
+
+    /// Sets the cell through which the maze is left, in the same way as `set_entrance`.
+    pub fn set_exit(&mut self, exit: Option<(usize, usize)>) {
+        let opening = self.replace_opening(self.exit, exit, self.entrance, true);
+        self.exit = opening;
+    }
+
+    /// Closes the border wall of the opening `old` (unless the `other` opening
+    /// shares it) and opens one along the border of cell `new`, which is returned
+    /// together with the direction of the wall that was opened. If the cell has
+    /// more than one side along the border, the first is used (or the `last`).
+    fn replace_opening(
+        &mut self,
+        old: Option<((usize, usize), Direction)>,
+        new: Option<(usize, usize)>,
+        other: Option<((usize, usize), Direction)>,
+        last: bool,
+    ) -> Option<((usize, usize), Direction)> {
+        let opening = new.map(|(i, j)| {
+            if !self.contains(i, j) {
+                panic!("Attempting to open a cell outside of the map");
+            }
+
+            let directions = self.get_border_directions(i, j);
+            let direction = if last {
+                directions.last()
+            } else {
+                directions.first()
+            };
+            match direction {
+                Some(direction) => ((i, j), *direction),
+                None => panic!("Attempting to open a cell that isn't along the edge of the map"),
+            }
+        });
+
+        if let Some(((i, j), direction)) = old {
+            if old != other {
+                self.get_cell_mut(i, j).set_open(direction, false);
+            }
+        }
+        if let Some(((i, j), direction)) = opening {
+            self.get_cell_mut(i, j).set_open(direction, true);
+        }
+
+        opening
+    }
+
+    /// Returns the directions in which cell <`i`, `j`> borders the outside of the
+    /// map, i.e. the sides of the cell that lie along the edge of the grid (stairs
+    /// on a multi-level map never lead outside, and neither does the inner side of
+    /// a polar map's innermost ring, which faces the closed center).
+    pub fn get_border_directions(&self, i: usize, j: usize) -> Vec<Direction> {
+        self.topology
+            .get_directions(self.dimensions, i, j)
+            .into_iter()
+            .filter(|direction| {
+                !matches!(
+                    direction,
+                    Direction::Up | Direction::Down | Direction::Inward
+                )
+            })
+            .filter(|direction| {
+                self.topology
+                    .get_neighbor(self.dimensions, i, j, *direction)
+                    .is_none()
+            })
+            .collect()
+    }
+
+    /// Places the entrance and the exit of the maze as far apart as possible, at
+    /// the two cells along the edge of the map with the longest path between them
+    /// (see `distance::longest_path_between`), and returns that path. Returns `None`
+    /// if no cell lies along the edge of the map, as on a cube.
+    pub fn place_entrance_and_exit(&mut self) -> Option<Vec<(usize, usize)>> {
+        let border: Vec<(usize, usize)> = self
+            .get_all_grid_indices()
+            .into_iter()
+            .filter(|(i, j)| !self.get_border_directions(*i, *j).is_empty())
+            .collect();
+
+        // Every border cell is part of the maze, so this can't fail
+        let path = distance::longest_path_between(self, &border).unwrap();
+        if path.is_empty() {
+            return None;
+        }
+
+        self.set_entrance(Some(path[0]));
+        self.set_exit(Some(path[path.len() - 1]));
+        Some(path)
+    }
+
+    /// Returns the grid indices of the cell that generators should start carving
+    /// from: either the start cell (if one was set) or a random cell.
+    pub fn get_start_grid_indices(&self, rng: &mut dyn RngCore) -> (usize, usize) {
+        match self.start {
+            Some(start) => start,
+            None => self.get_random_grid_indices(rng),
+        }
+    }
+
     /// Returns a random pair of valid grid indices.
-    pub(crate) fn get_random_grid_indices(&self) -> (usize, usize) {
-        let mut rng = rand::thread_rng();
-        (
-            rng.gen_range(0, self.dimensions.0),
-            rng.gen_range(0, self.dimensions.1),
-        )
+    pub fn get_random_grid_indices(&self, rng: &mut dyn RngCore) -> (usize, usize) {
+        // Keep drawing until we land on a cell that is part of the map
+        loop {
+            let i = rng.gen_range(0, self.dimensions.0);
+            let j = rng.gen_range(0, self.dimensions.1);
+
+            if self.contains(i, j) {
+                return (i, j);
+            }
+        }
     }
 
     /// Returns an immutable reference to cell <`i`, `j`>, where `i` is the row
     /// and `j` is the column.
     pub fn get_cell(&self, i: usize, j: usize) -> &Cell {
-        &self.terrain[i * self.dimensions.1 + j]
+        &self.terrain[self.grid_to_absolute_indices(i, j)]
     }
 
     /// Returns a mutable reference to cell <`i`, `j`>, where `i` is the row
     /// and `j` is the column.
     pub fn get_cell_mut(&mut self, i: usize, j: usize) -> &mut Cell {
-        &mut self.terrain[i * self.dimensions.1 + j]
+        let idx = self.grid_to_absolute_indices(i, j);
+        &mut self.terrain[idx]
     }
 
     pub fn visit(&mut self, i: usize, j: usize) {
         self.get_cell_mut(i, j).visited = true;
     }
 
-    pub(crate) fn get_unvisited_neighbors(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
+    pub fn get_unvisited_neighbors(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
         self.get_neighbors(i, j)
-            .iter()
-            .cloned()
+            .into_iter()
+            .chain(self.get_tunnel_neighbors(i, j))
             .filter(|(ni, nj)| !self.get_cell(*ni, *nj).visited)
             .collect()
     }
 
-    pub(crate) fn get_visited_neighbors(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
+    pub fn get_visited_neighbors(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
         self.get_neighbors(i, j)
-            .iter()
-            .cloned()
+            .into_iter()
+            .chain(self.get_tunnel_neighbors(i, j))
             .filter(|(ni, nj)| self.get_cell(*ni, *nj).visited)
             .collect()
     }
 
+    /// Returns the indices of all of the cells that a new passage could reach from
+    /// cell <`i`, `j`> by tunneling underneath one of its neighbors. This is always
+    /// empty, unless the map is a weave maze.
+    pub fn get_tunnel_neighbors(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
+        if !self.weave {
+            return vec![];
+        }
+
+        self.topology
+            .get_directions(self.dimensions, i, j)
+            .into_iter()
+            .filter_map(|direction| {
+                let under = self.get_neighbor(i, j, direction)?;
+                let beyond = self.get_neighbor(under.0, under.1, direction)?;
+
+                if self.can_tunnel_under(under, direction) {
+                    Some(beyond)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the cell that is adjacent to cell <`i`, `j`> in the
+    /// specified `direction`, if there is one.
+    pub fn get_neighbor(&self, i: usize, j: usize, direction: Direction) -> Option<(usize, usize)> {
+        self.topology
+            .get_neighbor(self.dimensions, i, j, direction)
+            .filter(|(ni, nj)| self.contains(*ni, *nj))
+    }
+
     /// Returns the indices of all of the valid neighbors of cell <`i`, `j`>,
     /// respecting the borders of the map.
     pub fn get_neighbors(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
-        let mut neighbors = vec![];
+        let mut neighbors = self.topology.get_neighbors(self.dimensions, i, j);
+        if self.mask.is_some() {
+            neighbors.retain(|(ni, nj)| self.contains(*ni, *nj));
+        }
+        neighbors
+    }
+
+    /// Returns the indices of all of the neighbors of cell <`i`, `j`> that we
+    /// can travel to from this cell.
+    pub fn get_open_neighbors(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
+        let cell = self.get_cell(i, j);
+
+        self.topology
+            .get_directions(self.dimensions, i, j)
+            .into_iter()
+            .filter(|direction| cell.is_open(*direction))
+            .filter_map(|direction| {
+                let neighbor = self
+                    .topology
+                    .get_neighbor(self.dimensions, i, j, direction)?;
+
+                // If the passage doesn't lead into the neighbor, it must run underneath
+                // it and come back up on the other side
+                let next = self.get_cell(neighbor.0, neighbor.1);
+                match direction.get_opposite() {
+                    Some(backward) if next.tunnel && !next.is_open(backward) => {
+                        self.get_neighbor(neighbor.0, neighbor.1, direction)
+                    }
+                    _ => Some(neighbor),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the indices of all of the neighbors of cell <`i`, `j`> that are
+    /// separated from it by a wall.
+    pub fn get_closed_neighbors(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
+        let cell = self.get_cell(i, j);
+
+        self.topology
+            .get_directions(self.dimensions, i, j)
+            .into_iter()
+            .filter(|direction| !cell.is_open(*direction))
+            .filter_map(|direction| self.get_neighbor(i, j, direction))
+            .collect()
+    }
+}
+
+/// Every cell of a map is a node, identified by its grid indices. Passages can be
+/// carved between adjacent cells and, in weave mazes, underneath them.
+impl Graph for Map {
+    type Node = (usize, usize);
+
+    fn get_nodes(&self) -> Vec<(usize, usize)> {
+        self.get_all_grid_indices()
+    }
 
-        // Top
-        if i > 0 {
-            neighbors.push((i - 1, j + 0))
+    fn get_node_count(&self) -> usize {
+        self.get_cell_count()
+    }
+
+    fn contains(&self, node: (usize, usize)) -> bool {
+        Map::contains(self, node.0, node.1)
+    }
+
+    fn get_neighbors(&self, node: (usize, usize)) -> Vec<(usize, usize)> {
+        Map::get_neighbors(self, node.0, node.1)
+    }
+
+    fn get_open_neighbors(&self, node: (usize, usize)) -> Vec<(usize, usize)> {
+        Map::get_open_neighbors(self, node.0, node.1)
+    }
+
+    fn get_closed_neighbors(&self, node: (usize, usize)) -> Vec<(usize, usize)> {
+        Map::get_closed_neighbors(self, node.0, node.1)
+    }
+
+    fn get_visited_neighbors(&self, node: (usize, usize)) -> Vec<(usize, usize)> {
+        Map::get_visited_neighbors(self, node.0, node.1)
+    }
+
+    fn get_unvisited_neighbors(&self, node: (usize, usize)) -> Vec<(usize, usize)> {
+        Map::get_unvisited_neighbors(self, node.0, node.1)
+    }
+
+    /// In weave mazes, a north-south corridor can cross an east-west one at any
+    /// cell that has all four neighbors.
+    fn get_crossings(&self, node: (usize, usize)) -> Vec<[(usize, usize); 2]> {
+        if !self.weave {
+            return vec![];
         }
 
-        // Bottom
-        if i < self.dimensions.0 - 1 {
-            neighbors.push((i + 1, j + 0))
+        let around: Vec<(usize, usize)> = [
+            Direction::North,
+            Direction::South,
+            Direction::West,
+            Direction::East,
+        ]
+        .iter()
+        .filter_map(|direction| self.get_neighbor(node.0, node.1, *direction))
+        .collect();
+
+        match around.len() {
+            4 => vec![[around[0], around[1]], [around[2], around[3]]],
+            _ => vec![],
         }
+    }
 
-        // Left
-        if j > 0 {
-            neighbors.push((i + 0, j - 1))
+    /// Passages that tunnel underneath another cell cover twice the distance.
+    fn get_cost(&self, from: (usize, usize), to: (usize, usize)) -> usize {
+        match self
+            .topology
+            .get_direction_between(self.dimensions, from, to)
+        {
+            Some(_) => 1,
+            None => 2,
         }
+    }
 
-        // Right
-        if j < self.dimensions.1 - 1 {
-            neighbors.push((i + 0, j + 1))
+    /// The Manhattan distance between two cells, on maps where every step moves
+    /// one row or one column (and never both, nor around the edges).
+    fn estimate_cost(&self, from: (usize, usize), to: (usize, usize)) -> usize {
+        match self.topology {
+            Topology::Square | Topology::Triangle => from.0.abs_diff(to.0) + from.1.abs_diff(to.1),
+            _ => 0,
         }
+    }
 
-        neighbors
+    fn is_visited(&self, node: (usize, usize)) -> bool {
+        self.get_cell(node.0, node.1).visited
     }
 
-    pub fn get_open_neighbors(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
-        let mut neighbors = vec![];
-        let cell = self.get_cell(i, j);
+    fn visit(&mut self, node: (usize, usize)) {
+        Map::visit(self, node.0, node.1);
+    }
 
-        // Top
-        if cell.n && i > 0 {
-            neighbors.push((i - 1, j + 0));
+    fn open_path_between(&mut self, to: (usize, usize), from: (usize, usize)) {
+        Map::open_path_between(self, to, from);
+    }
+
+    fn get_start_node(&self, rng: &mut dyn RngCore) -> (usize, usize) {
+        self.get_start_grid_indices(rng)
+    }
+}
+
+/// Writes the ASCII art representation of a single row of cells to `out`: the
+/// line of walls above the row, followed by the row itself.
+fn write_ascii_row(out: &mut impl std::fmt::Write, row: &[Cell]) -> std::fmt::Result {
+    // Print the line above this row
+    for cell in row.iter() {
+        // Can we move up from this cell?
+        if cell.is_open(Direction::North) {
+            write!(out, "◼◻◻")?;
+        } else {
+            write!(out, "◼◼◼")?;
         }
+    }
+    writeln!(out, "◼")?;
 
-        // Bottom
-        if cell.s && i < self.dimensions.0 - 1 {
-            neighbors.push((i + 1, j + 0))
+    // Print the middle (cell) line (twice, because of unicode spacing)
+    for line in 0..2 {
+        for cell in row.iter() {
+            if cell.visited {
+                // Can we move left from this cell?
+                if cell.is_open(Direction::West) {
+                    write!(out, "◻")?;
+                } else {
+                    write!(out, "◼")?;
+                }
+
+                // Mark any stairs on the first line: up on the left, down on the right
+                let up = line == 0 && cell.is_open(Direction::Up);
+                let down = line == 0 && cell.is_open(Direction::Down);
+                write!(
+                    out,
+                    "{}{}",
+                    if up { "▲" } else { "◻" },
+                    if down { "▼" } else { "◻" }
+                )?;
+            } else {
+                write!(out, "◼◼◼")?;
+            }
         }
 
-        // Left
-        if cell.w && j > 0 {
-            neighbors.push((i + 0, j - 1))
+        // Can we move right from the last cell (i.e. around to the other edge)?
+        match row.last() {
+            Some(cell) if cell.is_open(Direction::East) => writeln!(out, "◻")?,
+            _ => writeln!(out, "◼")?,
         }
+    }
+
+    Ok(())
+}
 
-        // Right
-        if cell.e && j < self.dimensions.1 - 1 {
-            neighbors.push((i + 0, j + 1))
+/// Writes the line of walls below the last `row` of a maze to `out`.
+fn write_ascii_bottom(out: &mut impl std::fmt::Write, row: &[Cell]) -> std::fmt::Result {
+    for cell in row.iter() {
+        // Can we move down from this cell (i.e. around to the other edge)?
+        if cell.is_open(Direction::South) {
+            write!(out, "◼◻◻")?;
+        } else {
+            write!(out, "◼◼◼")?;
         }
+    }
+    writeln!(out, "◼")
+}
 
-        neighbors
+/// Saves an ASCII art representation of a maze that is streamed in one row at a
+/// time (for example, from `EllerRows`) to `path`. Only a single row is held in
+/// memory at any given time, so mazes of arbitrary height can be written.
+pub fn save_ascii_rows(
+    path: &Path,
+    rows: impl IntoIterator<Item = Vec<Cell>>,
+) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+
+    // Add a BOM unicode character (maybe not always necessary?)
+    file.write_all(&[0xEF, 0xBB, 0xBF])?;
+
+    let mut last = vec![];
+    let mut text = String::new();
+
+    for row in rows {
+        text.clear();
+        write_ascii_row(&mut text, &row).unwrap();
+        file.write_all(text.as_bytes())?;
+
+        last = row;
     }
+
+    text.clear();
+    write_ascii_bottom(&mut text, &last).unwrap();
+    file.write_all(text.as_bytes())?;
+
+    file.flush()
 }
 
 impl std::fmt::Debug for Map {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in 0..self.dimensions.0 {
-            // Print the line above this row
-            for col in 0..self.dimensions.1 {
-                // Can we move up from this cell?
-                if self.get_cell(row, col).n {
-                    write!(f, "◼◻◻")?;
-                } else {
-                    write!(f, "◼◼◼")?;
-                }
-                if col == self.dimensions.1 - 1 {
-                    write!(f, "◼\n")?;
-                }
-            }
+        // The levels of a multi-level map are drawn side by side
+        if let Topology::Levels(levels) = self.topology {
+            let mut drawings = vec![String::new(); levels];
+            let rows = self.topology.get_rows_per_level(self.dimensions);
 
-            // Print the middle (cell) line (twice, because of unicode spacing)
-            for _ in 0..2 {
-                for col in 0..self.dimensions.1 {
-                    if self.get_cell(row, col).visited {
-                        // Can we move left from this cell?
-                        if self.get_cell(row, col).w {
-                            write!(f, "◻◻◻")?;
-                        } else {
-                            write!(f, "◼◻◻")?;
-                        }
-                    } else {
-                        write!(f, "◼◼◼")?;
-                    }
-                    if col == self.dimensions.1 - 1 {
-                        write!(f, "◼\n")?;
-                    }
+            for (level, drawing) in drawings.iter_mut().enumerate() {
+                let start = level * rows * self.dimensions.1;
+                let end = start + rows * self.dimensions.1;
+
+                for row in self.terrain[start..end].chunks(self.dimensions.1) {
+                    write_ascii_row(drawing, row)?;
                 }
+                write_ascii_bottom(drawing, &self.terrain[end - self.dimensions.1..end])?;
             }
 
-            // If this is the last row, add an additional line of chars below
-            if row == self.dimensions.0 - 1 {
-                for _ in 0..self.dimensions.1 {
-                    write!(f, "◼◼◼")?;
-                }
-                write!(f, "◼\n")?;
+            // Stitch the drawings together, one line at a time
+            let mut lines: Vec<_> = drawings.iter().map(|drawing| drawing.lines()).collect();
+            for _ in 0..rows * 3 + 1 {
+                let line: Vec<_> = lines.iter_mut().filter_map(|l| l.next()).collect();
+                writeln!(f, "{}", line.join(" "))?;
             }
+            return Ok(());
         }
-        Ok(())
+
+        // Only square cells can be drawn with ASCII art (see `save_svg` instead)
+        if !matches!(self.topology, Topology::Square | Topology::Wrapped(_)) {
+            return write!(
+                f,
+                "Map {{ topology: {:?}, dimensions: {:?} }}",
+                self.topology, self.dimensions
+            );
+        }
+
+        let rows: Vec<_> = self.terrain.chunks(self.dimensions.1).collect();
+        for row in rows.iter() {
+            write_ascii_row(f, row)?;
+        }
+        write_ascii_bottom(f, rows.last().unwrap_or(&&[][..]))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::distance::distances;
+    use crate::generators::{Backtracking, Eller, EllerRows};
+    use crate::search::breadth_first;
+    use crate::topology::Wrap;
 
     #[test]
     fn test_absolute_to_grid_indices() {
@@ -346,8 +1025,8 @@ mod tests {
         let to = map.get_cell(0, 0);
         let from = map.get_cell(1, 0);
 
-        assert!(to.s);
-        assert!(from.n);
+        assert!(to.is_open(Direction::South));
+        assert!(from.is_open(Direction::North));
     }
 
     #[test]
@@ -358,7 +1037,351 @@ mod tests {
         let to = map.get_cell(0, 0);
         let from = map.get_cell(0, 1);
 
-        assert!(to.e);
-        assert!(from.w);
+        assert!(to.is_open(Direction::East));
+        assert!(from.is_open(Direction::West));
+    }
+
+    #[test]
+    fn test_save_ascii_rows() {
+        let dir = std::env::temp_dir();
+        let streamed = dir.join(format!("maze_rows_{}.txt", std::process::id()));
+        let built = dir.join(format!("maze_map_{}.txt", std::process::id()));
+
+        // Streaming the rows to disk draws the same maze as building it all at once
+        let rows = EllerRows::from_rng_with_height(6, 10, ChaCha8Rng::seed_from_u64(5));
+        save_ascii_rows(&streamed, rows).unwrap();
+
+        let mut map = Map::empty((10, 6));
+        map.build_maze(&Eller {}, &mut ChaCha8Rng::seed_from_u64(5));
+        map.save_ascii(&built).unwrap();
+
+        let text = std::fs::read_to_string(&streamed).unwrap();
+        assert_eq!(text, std::fs::read_to_string(&built).unwrap());
+        assert_eq!(text.lines().count(), 10 * 3 + 1);
+
+        std::fs::remove_file(streamed).unwrap();
+        std::fs::remove_file(built).unwrap();
+    }
+
+    #[test]
+    fn test_close_path_between() {
+        let mut map = Map::empty((4, 4));
+        map.open_all_paths();
+        map.close_path_between((1, 1), (1, 2));
+
+        let to = map.get_cell(1, 1);
+        let from = map.get_cell(1, 2);
+
+        assert_eq!(map.get_open_neighbors(1, 1), vec![(0, 1), (2, 1), (1, 0)]);
+        assert_eq!(map.get_open_neighbors(1, 2), vec![(0, 2), (2, 2), (1, 3)]);
+        assert!(!to.is_open(Direction::East));
+        assert!(!from.is_open(Direction::West));
+    }
+
+    #[test]
+    fn test_from_seed() {
+        let a = Map::from_seed((12, 15), 1234);
+        let b = Map::from_seed((12, 15), 1234);
+        let c = Map::from_seed((12, 15), 4321);
+
+        assert_eq!(format!("{:?}", a), format!("{:?}", b));
+        assert_ne!(format!("{:?}", a), format!("{:?}", c));
+
+        // The same seed produces the same maze everywhere, not just within one build
+        let map = Map::from_seed((3, 3), 42);
+        let passages: Vec<_> = map
+            .get_all_grid_indices()
+            .into_iter()
+            .map(|(i, j)| map.get_open_neighbors(i, j))
+            .collect();
+        assert_eq!(
+            passages,
+            vec![
+                vec![(1, 0)],
+                vec![(1, 1), (0, 2)],
+                vec![(0, 1)],
+                vec![(0, 0), (1, 1)],
+                vec![(0, 1), (2, 1), (1, 0), (1, 2)],
+                vec![(1, 1)],
+                vec![(2, 1)],
+                vec![(1, 1), (2, 0), (2, 2)],
+                vec![(2, 1)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hex_open_path_between() {
+        let mut map = Map::with_topology((4, 4), Topology::Hex);
+        map.open_path_between((1, 1), (2, 2));
+
+        assert!(map.get_cell(1, 1).is_open(Direction::SouthEast));
+        assert!(map.get_cell(2, 2).is_open(Direction::NorthWest));
+        assert_eq!(map.get_open_neighbors(2, 2), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn test_polar() {
+        let map = Map::with_topology((6, 32), Topology::Polar);
+        assert_eq!(map.get_cell_count(), 8 + 16 + 16 + 32 + 32 + 32);
+        assert_eq!(map.get_all_grid_indices().len(), map.get_cell_count());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_polar_start_outside_ring() {
+        let mut map = Map::with_topology((6, 32), Topology::Polar);
+        map.set_start(Some((0, 8)));
+    }
+
+    #[test]
+    fn test_levels() {
+        // The only way from the ground floor to the top floor is up the stairs
+        let mut map = Map::with_levels((1, 1), 3);
+        map.open_all_paths();
+        assert_eq!(
+            breadth_first(&map, (0, 0), (2, 0)),
+            Ok(vec![(2, 0), (1, 0), (0, 0)])
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_levels_fewer_rows() {
+        Map::with_topology((2, 3), Topology::Levels(5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_levels_uneven_rows() {
+        Map::with_topology((5, 3), Topology::Levels(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_levels_none() {
+        Map::with_levels((4, 4), 0);
+    }
+
+    #[test]
+    fn test_cube() {
+        // Walking off the top of the front face leads onto the top face, and from
+        // there onto the back face
+        let mut map = Map::with_cube(1);
+        map.open_all_paths();
+        assert_eq!(breadth_first(&map, (0, 0), (2, 0)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_levels_ascii() {
+        let mut map = Map::with_levels((1, 2), 2);
+        map.open_all_paths();
+
+        let ascii = format!("{:?}", map);
+        let lines: Vec<_> = ascii.lines().collect();
+        assert_eq!(lines.len(), 4);
+
+        // Both levels are drawn side by side, with stairs in each cell
+        assert_eq!(lines[1], "◼▲◻◻▲◻◼ ◼◻▼◻◻▼◼");
+    }
+
+    #[test]
+    fn test_wrapped() {
+        // The shortest way between the two ends of a cylinder is around the back
+        let mut map = Map::with_topology((1, 5), Topology::Wrapped(Wrap::Horizontal));
+        map.open_all_paths();
+        assert_eq!(breadth_first(&map, (0, 0), (0, 4)).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_wrapped_ascii() {
+        let mut map = Map::with_topology((3, 3), Topology::Wrapped(Wrap::Both));
+        map.open_path_between((0, 0), (0, 2));
+        map.open_path_between((0, 1), (2, 1));
+        for (i, j) in map.get_all_grid_indices() {
+            map.visit(i, j);
+        }
+
+        // Passages around the edges of the map show up as gaps in the border
+        let ascii = format!("{:?}", map);
+        let lines: Vec<_> = ascii.lines().collect();
+        assert_eq!(lines[0], "◼◼◼◼◻◻◼◼◼◼");
+        assert_eq!(lines[1], "◻◻◻◼◻◻◼◻◻◻");
+        assert_eq!(lines[9], "◼◼◼◼◻◻◼◼◼◼");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_masked_disconnected() {
+        // Two separate regions, which no maze could ever join up
+        let mut map = Map::empty((3, 3));
+        map.set_mask(Some(Mask::from_ascii("..X\n..X\nXX.")));
+    }
+
+    #[test]
+    fn test_masked_connected() {
+        // The same shape is connected on a hexagonal map, where (1, 1) borders (2, 2)
+        let mut map = Map::with_topology((3, 3), Topology::Hex);
+        map.set_mask(Some(Mask::from_ascii("..X\n..X\nXX.")));
+        assert_eq!(map.get_cell_count(), 5);
+    }
+
+    #[test]
+    fn test_unmasked_disconnected() {
+        // A single column of triangles doesn't border itself, but without a mask,
+        // that's the topology's doing rather than the mask's
+        let mut map = Map::with_topology((3, 1), Topology::Triangle);
+        map.set_mask(None);
+        assert_eq!(map.get_cell_count(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_masked_start() {
+        let mut map = Map::empty((2, 2));
+        map.set_mask(Some(Mask::from_ascii("X.\n..")));
+        map.set_start(Some((0, 0)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_masked_after_start() {
+        let mut map = Map::empty((2, 2));
+        map.set_start(Some((0, 0)));
+        map.set_mask(Some(Mask::from_ascii("X.\n..")));
+    }
+
+    #[test]
+    fn test_weave_tunnel() {
+        let mut map = Map::empty((3, 3));
+        map.set_weave(true);
+
+        // Tunneling requires a straight corridor to pass underneath
+        map.open_path_between((1, 1), (0, 1));
+        map.open_path_between((1, 1), (2, 1));
+        map.visit(1, 1);
+        assert_eq!(map.get_tunnel_neighbors(1, 0), vec![(1, 2)]);
+        assert!(map.get_tunnel_neighbors(0, 0).is_empty());
+
+        // The tunnel skips over the middle cell, which only leads north and south
+        map.open_path_between((1, 0), (1, 2));
+        assert!(map.get_cell(1, 1).has_tunnel());
+        assert_eq!(map.get_open_neighbors(1, 0), vec![(1, 2)]);
+        assert_eq!(map.get_open_neighbors(1, 2), vec![(1, 0)]);
+        assert_eq!(map.get_open_neighbors(1, 1), vec![(0, 1), (2, 1)]);
+        assert_eq!(
+            breadth_first(&map, (1, 0), (1, 2)),
+            Ok(vec![(1, 2), (1, 0)])
+        );
+
+        map.close_path_between((1, 2), (1, 0));
+        assert!(!map.get_cell(1, 1).has_tunnel());
+        assert!(map.get_open_neighbors(1, 0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_weave_without_corridor() {
+        let mut map = Map::empty((3, 3));
+        map.set_weave(true);
+        map.visit(1, 1);
+        map.open_path_between((1, 0), (1, 2));
+    }
+
+    #[test]
+    fn test_entrance_and_exit() {
+        let mut map = Map::empty((3, 3));
+        assert_eq!(map.get_border_directions(1, 1), vec![]);
+        assert_eq!(
+            map.get_border_directions(0, 2),
+            vec![Direction::North, Direction::East]
+        );
+
+        // A corner cell opens onto its first side along the border, or its last
+        map.set_entrance(Some((0, 2)));
+        map.set_exit(Some((1, 0)));
+        assert_eq!(map.get_entrance(), Some((0, 2)));
+        assert_eq!(map.get_exit(), Some((1, 0)));
+        assert!(map.get_cell(0, 2).is_open(Direction::North));
+        assert!(map.get_cell(1, 0).is_open(Direction::West));
+
+        // The openings lead outside, not to another cell
+        assert!(map.get_open_neighbors(0, 2).is_empty());
+        assert!(map.get_open_neighbors(1, 0).is_empty());
+
+        // Moving the entrance closes the old opening
+        map.set_entrance(Some((2, 2)));
+        assert!(!map.get_cell(0, 2).is_open(Direction::North));
+        assert!(map.get_cell(2, 2).is_open(Direction::South));
+
+        map.set_exit(Some((2, 2)));
+        assert!(map.get_cell(2, 2).is_open(Direction::East));
+        map.set_entrance(None);
+        assert_eq!(map.get_entrance(), None);
+        assert!(!map.get_cell(2, 2).is_open(Direction::South));
+        assert!(map.get_cell(2, 2).is_open(Direction::East));
+
+        // The openings show up in the ASCII drawing
+        let drawing = format!("{:?}", map);
+        assert!(drawing.lines().nth(7).unwrap().ends_with('◻'));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_entrance_inside() {
+        let mut map = Map::new((3, 3));
+        map.set_entrance(Some((1, 1)));
+    }
+
+    #[test]
+    fn test_place_entrance_and_exit() {
+        for seed in 0..5 {
+            let mut map = Map::from_seed((7, 9), seed);
+            let path = map.place_entrance_and_exit().unwrap();
+
+            let (entrance, exit) = (map.get_entrance().unwrap(), map.get_exit().unwrap());
+            assert_eq!((path[0], path[path.len() - 1]), (entrance, exit));
+            assert_eq!(
+                breadth_first(&map, entrance, exit).unwrap().len(),
+                path.len()
+            );
+
+            // No two cells along the border are farther apart
+            let border: Vec<_> = map
+                .get_all_grid_indices()
+                .into_iter()
+                .filter(|(i, j)| !map.get_border_directions(*i, *j).is_empty())
+                .collect();
+            for cell in border.iter() {
+                let (_, distance) = distances(&map, *cell)
+                    .unwrap()
+                    .get_farthest_of(&border)
+                    .unwrap();
+                assert!(distance < path.len());
+            }
+        }
+
+        // The openings of a polar maze lead out of the outer ring, not into the center
+        for seed in 0..10 {
+            let mut map = Map::with_topology((5, 16), Topology::Polar);
+            map.build_maze(&Backtracking {}, &mut ChaCha8Rng::seed_from_u64(seed));
+            assert!(map.place_entrance_and_exit().is_some());
+
+            for &(i, j) in [map.get_entrance().unwrap(), map.get_exit().unwrap()].iter() {
+                assert_eq!(i, 4);
+                assert!(map.get_cell(i, j).is_open(Direction::Outward));
+            }
+            for j in 0..map.get_row_width(0) {
+                assert!(!map.get_cell(0, j).is_open(Direction::Inward));
+            }
+        }
+        let map = Map::with_topology((5, 16), Topology::Polar);
+        assert_eq!(map.get_border_directions(0, 1), vec![]);
+        assert_eq!(map.get_border_directions(4, 1), vec![Direction::Outward]);
+
+        let mut map = Map::with_cube(2);
+        map.build_maze(&Backtracking {}, &mut ChaCha8Rng::seed_from_u64(0));
+        assert_eq!(map.place_entrance_and_exit(), None);
+        assert_eq!(map.get_entrance(), None);
     }
 }
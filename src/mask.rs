@@ -0,0 +1,291 @@
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+// References:
+// http://netpbm.sourceforge.net/doc/pbm.html
+// http://netpbm.sourceforge.net/doc/pgm.html
+
+/// A shape that restricts a map to a subset of its cells: generators only carve
+/// inside of the shape, and cells outside of it are never part of the maze.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mask {
+    // The dimensions of the mask, which must match those of the map it's applied to
+    dimensions: (usize, usize),
+
+    // Whether or not each cell is part of the shape (a 1D-array of flags,
+    // interpreted as a 2D-array)
+    enabled: Vec<bool>,
+}
+
+impl Mask {
+    /// Constructs a new mask with the specified `dimensions`, where cell <`i`, `j`>
+    /// is part of the shape if `f(i, j)` returns `true`.
+    pub fn from_fn(dimensions: (usize, usize), f: impl Fn(usize, usize) -> bool) -> Mask {
+        let enabled = (0..dimensions.0 * dimensions.1)
+            .map(|idx| f(idx / dimensions.1, idx % dimensions.1))
+            .collect();
+
+        Mask {
+            dimensions,
+            enabled,
+        }
+    }
+
+    /// Constructs a new mask from an ASCII art template, where each line is a row
+    /// and each character is a cell. Cells marked with an `X` are excluded from the
+    /// shape, and all other cells are included. Lines that are shorter than the
+    /// longest one are treated as if they were padded with `X`s.
+    pub fn from_ascii(template: &str) -> Mask {
+        let lines: Vec<Vec<char>> = template
+            .lines()
+            .map(|line| line.trim_end_matches('\r').chars().collect())
+            .collect();
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+
+        Mask::from_fn((lines.len(), width), |i, j| match lines[i].get(j) {
+            Some(c) => !c.eq_ignore_ascii_case(&'X'),
+            None => false,
+        })
+    }
+
+    /// Loads a mask from the PBM or PGM image at `path` (see `from_pnm`).
+    pub fn load_pnm(path: &Path) -> std::io::Result<Mask> {
+        Mask::from_pnm(&fs::read(path)?)
+    }
+
+    /// Constructs a new mask from a PBM (bitmap) or PGM (grayscale) image, in either
+    /// the plain (ASCII) or raw (binary) format, where each pixel is a cell. Dark
+    /// pixels make up the shape, so a maze will fill whatever is drawn in black.
+    pub fn from_pnm(bytes: &[u8]) -> std::io::Result<Mask> {
+        let mut reader = PnmReader { bytes, position: 0 };
+
+        let magic = reader.read_token()?;
+        if !["P1", "P2", "P4", "P5"].contains(&magic.as_str()) {
+            return Err(invalid(
+                "Only PBM (P1, P4) and PGM (P2, P5) images are supported",
+            ));
+        }
+
+        let width = reader.read_number()?;
+        let height = reader.read_number()?;
+
+        // Bitmaps don't store a maximum value, since each pixel is either 0 or 1
+        let bitmap = magic == "P1" || magic == "P4";
+        let maximum = if bitmap { 1 } else { reader.read_number()? };
+
+        let count = width
+            .checked_mul(height)
+            .ok_or_else(|| invalid("Image is too large"))?;
+
+        // Make sure that the image is long enough to hold every pixel (each of which
+        // takes up at least a byte, except in raw bitmaps) before making room for them
+        let needed = match magic.as_str() {
+            "P4" => width.div_ceil(8).checked_mul(height),
+            "P5" if maximum > 255 => count.checked_mul(2),
+            _ => Some(count),
+        };
+        match needed {
+            Some(needed) if needed <= bytes.len() - reader.position => (),
+            _ => return Err(invalid("Unexpected end of image")),
+        }
+
+        let mut dark = Vec::with_capacity(count);
+
+        match magic.as_str() {
+            // Plain bitmap: 1 is black, and the digits needn't be separated
+            "P1" => {
+                while dark.len() < count {
+                    match reader.read_byte()? {
+                        b'0' => dark.push(false),
+                        b'1' => dark.push(true),
+                        b'#' => reader.skip_comment(),
+                        byte if byte.is_ascii_whitespace() => (),
+                        _ => return Err(invalid("Unexpected character in PBM image")),
+                    }
+                }
+            }
+
+            // Raw bitmap: one bit per pixel (1 is black), and each row starts on a new byte
+            "P4" => {
+                reader.skip_whitespace_byte();
+                let stride = width.div_ceil(8);
+
+                for i in 0..height {
+                    for j in 0..width {
+                        let byte = reader.peek_byte(i * stride + j / 8)?;
+                        dark.push(byte & (0x80 >> (j % 8)) != 0);
+                    }
+                }
+            }
+
+            // Plain graymap: 0 is black and `maximum` is white
+            "P2" => {
+                for _ in 0..count {
+                    dark.push(reader.read_number()? * 2 < maximum);
+                }
+            }
+
+            // Raw graymap: one byte per pixel, or two (big-endian) for deeper images
+            "P5" => {
+                reader.skip_whitespace_byte();
+                let depth = if maximum > 255 { 2 } else { 1 };
+
+                for idx in 0..count {
+                    let mut value = 0;
+                    for offset in 0..depth {
+                        value = value << 8 | reader.peek_byte(idx * depth + offset)? as usize;
+                    }
+                    dark.push(value * 2 < maximum);
+                }
+            }
+
+            _ => unreachable!(),
+        }
+
+        Ok(Mask {
+            dimensions: (height, width),
+            enabled: dark,
+        })
+    }
+
+    /// Returns the dimensions of the mask.
+    pub fn get_dimensions(&self) -> (usize, usize) {
+        self.dimensions
+    }
+
+    /// Returns `true` if cell <`i`, `j`> is part of the shape, and `false` otherwise.
+    pub fn is_enabled(&self, i: usize, j: usize) -> bool {
+        i < self.dimensions.0 && j < self.dimensions.1 && self.enabled[i * self.dimensions.1 + j]
+    }
+
+    /// Returns the number of cells that are part of the shape.
+    pub fn get_count(&self) -> usize {
+        self.enabled.iter().filter(|enabled| **enabled).count()
+    }
+}
+
+/// Returns an error describing a malformed image.
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// A cursor over the bytes of a PBM or PGM image.
+struct PnmReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PnmReader<'a> {
+    fn read_byte(&mut self) -> std::io::Result<u8> {
+        let byte = self.peek_byte(0)?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    /// Returns the byte `offset` bytes past the current position, without moving.
+    fn peek_byte(&self, offset: usize) -> std::io::Result<u8> {
+        self.bytes
+            .get(self.position + offset)
+            .cloned()
+            .ok_or_else(|| invalid("Unexpected end of image"))
+    }
+
+    /// Skips the rest of the current line, which started with a `#`.
+    fn skip_comment(&mut self) {
+        while let Ok(byte) = self.read_byte() {
+            if byte == b'\n' {
+                break;
+            }
+        }
+    }
+
+    /// Skips the single whitespace character that separates the header of a raw
+    /// image from its pixels.
+    fn skip_whitespace_byte(&mut self) {
+        self.position += 1;
+    }
+
+    /// Reads the next whitespace-separated token, skipping any comments.
+    fn read_token(&mut self) -> std::io::Result<String> {
+        let mut token = String::new();
+
+        loop {
+            match self.peek_byte(0) {
+                Ok(b'#') if token.is_empty() => self.skip_comment(),
+                Ok(byte) if byte.is_ascii_whitespace() => {
+                    if !token.is_empty() {
+                        return Ok(token);
+                    }
+                    self.position += 1;
+                }
+                Ok(byte) => {
+                    token.push(byte as char);
+                    self.position += 1;
+                }
+                Err(error) if token.is_empty() => return Err(error),
+                Err(_) => return Ok(token),
+            }
+        }
+    }
+
+    fn read_number(&mut self) -> std::io::Result<usize> {
+        self.read_token()?
+            .parse()
+            .map_err(|_| invalid("Expected a number in image header"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_ascii() {
+        let mask = Mask::from_ascii("X..\n...X\n.");
+
+        assert_eq!(mask.get_dimensions(), (3, 4));
+        assert_eq!(mask.get_count(), 6);
+        assert!(!mask.is_enabled(0, 0));
+        assert!(mask.is_enabled(0, 1));
+        assert!(!mask.is_enabled(0, 3));
+        assert!(!mask.is_enabled(1, 3));
+        assert!(!mask.is_enabled(2, 1));
+    }
+
+    #[test]
+    fn test_from_pnm() {
+        let expected = Mask::from_ascii(".X.\nXX.");
+
+        // The same image in each of the supported formats
+        let plain_pbm = b"P1\n# a comment\n3 2\n101\n0 0 1\n";
+        let raw_pbm = b"P4 3 2\n\xa0\x20";
+        let plain_pgm = b"P2\n3 2\n255\n0 255 0\n200 255 10\n";
+        let raw_pgm = b"P5 3 2 255\n\x00\xff\x00\xc8\xff\x0a";
+
+        for bytes in [&plain_pbm[..], &raw_pbm[..], &plain_pgm[..], &raw_pgm[..]].iter() {
+            assert_eq!(Mask::from_pnm(bytes).unwrap(), expected);
+        }
+
+        assert!(Mask::from_pnm(b"P6 1 1 255\n\x00\x00\x00").is_err());
+        assert!(Mask::from_pnm(b"P5 2 2 255\n\x00").is_err());
+    }
+
+    #[test]
+    fn test_from_pnm_oversized() {
+        // Headers that claim far more pixels than the image holds (or than fit in
+        // memory at all) are rejected before anything is allocated
+        for bytes in [
+            &b"P5\n4294967295 4294967295\n255\n\0"[..],
+            &b"P5 18446744073709551615 2 255\n\0"[..],
+            &b"P4 100000 100000\n\0"[..],
+            &b"P2 1000000 1000000 255\n0 0 0"[..],
+            &b"P5 2 2 65535\n\0\0\0\0"[..],
+        ]
+        .iter()
+        {
+            let error = Mask::from_pnm(bytes).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::map::Map;
+use crate::topology::{Direction, Topology};
+use std::fmt::Write;
+
+// Reference: https://en.wikipedia.org/wiki/Wavefront_.obj_file
+
+/// A point or direction in 3D space.
+type Vector = [f32; 3];
+
+/// The height of each wall above the surface of the cube, relative to the size of a cell.
+const WALL_HEIGHT: f32 = 0.5;
+
+/// The thickness of each wall, relative to the size of a cell.
+const WALL_THICKNESS: f32 = 0.15;
+
+fn add(a: Vector, b: Vector) -> Vector {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn scale(a: Vector, s: f32) -> Vector {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn cross(a: Vector, b: Vector) -> Vector {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Writes a quad to `obj`, whose `corners` wind counterclockwise when seen from the
+/// outside. Every quad gets its own vertices, so `count` tracks how many have been
+/// written so far (faces refer to vertices by their 1-based index).
+fn write_quad(obj: &mut String, count: &mut usize, corners: [Vector; 4]) -> std::fmt::Result {
+    for [x, y, z] in corners.iter() {
+        writeln!(obj, "v {:.4} {:.4} {:.4}", x, y, z)?;
+    }
+    writeln!(
+        obj,
+        "f {} {} {} {}",
+        *count + 1,
+        *count + 2,
+        *count + 3,
+        *count + 4
+    )?;
+
+    *count += 4;
+    Ok(())
+}
+
+/// Writes a box to `obj` by extruding the quad `base` (which winds counterclockwise
+/// around `normal`) along `normal` by `height` units.
+fn write_box(
+    obj: &mut String,
+    count: &mut usize,
+    base: [Vector; 4],
+    normal: Vector,
+    height: f32,
+) -> std::fmt::Result {
+    let top = base.map(|corner| add(corner, scale(normal, height)));
+
+    write_quad(obj, count, [base[3], base[2], base[1], base[0]])?;
+    write_quad(obj, count, top)?;
+
+    for k in 0..4 {
+        let next = (k + 1) % 4;
+        write_quad(obj, count, [base[k], base[next], top[next], top[k]])?;
+    }
+
+    Ok(())
+}
+
+/// Renders a cube maze as a Wavefront OBJ mesh, where each cell is `size` units
+/// across: a solid cube, with a raised wall along every side of a cell that doesn't
+/// lead to an open passage. The walls are slightly longer than a cell, so that they
+/// overlap (rather than leave gaps) wherever they meet.
+pub fn render(map: &Map, size: f32) -> String {
+    let topology = map.get_topology();
+    let dimensions = map.get_dimensions();
+    assert_eq!(
+        topology,
+        Topology::Cube,
+        "Only cube mazes can be rendered as a 3D mesh"
+    );
+
+    let mut obj = String::new();
+    let mut count = 0;
+    writeln!(obj, "# {} cube maze", dimensions.1).unwrap();
+    writeln!(obj, "o maze").unwrap();
+
+    // The body of the cube, one face at a time
+    let n = dimensions.1 as f32;
+    for face in 0..6 {
+        let (origin, u, v) = Topology::get_cube_frame(dimensions, face * dimensions.1, 0);
+        let (u, v) = (scale(u, n), scale(v, n));
+        let corners = [
+            origin,
+            add(origin, v),
+            add(add(origin, u), v),
+            add(origin, u),
+        ];
+
+        write_quad(&mut obj, &mut count, corners.map(|c| scale(c, size))).unwrap();
+    }
+
+    let (height, thickness) = (WALL_HEIGHT * size, WALL_THICKNESS * size);
+
+    for (i, j) in map.get_all_grid_indices() {
+        let cell = map.get_cell(i, j);
+        let (corner, u, v) = Topology::get_cube_frame(dimensions, i, j);
+        let normal = cross(v, u);
+
+        for direction in topology.get_directions(dimensions, i, j) {
+            // Shared walls are only built once, from the cell that comes first
+            let owned = match topology.get_neighbor(dimensions, i, j, direction) {
+                Some(neighbor) => (i, j) < neighbor,
+                None => true,
+            };
+            if !owned || cell.is_open(direction) {
+                continue;
+            }
+
+            let (from, along) = match direction {
+                Direction::North => (corner, u),
+                Direction::South => (add(corner, v), u),
+                Direction::West => (corner, v),
+                _ => (add(corner, u), v),
+            };
+
+            // Center the wall on the side of the cell, and extend it past both ends
+            let from = add(scale(from, size), scale(along, -0.5 * thickness));
+            let to = add(from, scale(along, size + thickness));
+            let across = scale(cross(normal, along), 0.5 * thickness);
+            let base = [
+                add(from, scale(across, -1.0)),
+                add(to, scale(across, -1.0)),
+                add(to, across),
+                add(from, across),
+            ];
+
+            write_box(&mut obj, &mut count, base, normal, height).unwrap();
+        }
+    }
+
+    obj
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        // A cube with a single cell per face has 12 walls, one along each edge
+        let mut map = Map::with_cube(1);
+        let obj = render(&map, 10.0);
+        assert_eq!(
+            obj.lines().filter(|l| l.starts_with("f ")).count(),
+            6 + 12 * 6
+        );
+        assert_eq!(
+            obj.lines().filter(|l| l.starts_with("v ")).count(),
+            4 * (6 + 12 * 6)
+        );
+
+        // Every face refers to vertices that have already been written
+        let mut vertices = 0;
+        for line in obj.lines() {
+            if line.starts_with("v ") {
+                vertices += 1;
+            } else if let Some(indices) = line.strip_prefix("f ") {
+                assert!(indices
+                    .split(' ')
+                    .all(|idx| (1..=vertices).contains(&idx.parse::<usize>().unwrap())));
+            }
+        }
+
+        map.open_all_paths();
+        let obj = render(&map, 10.0);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 6);
+
+        // The body of the cube spans the full size
+        let coordinates: Vec<f32> = obj
+            .lines()
+            .filter_map(|l| l.strip_prefix("v "))
+            .flat_map(|l| l.split(' ').map(|x| x.parse::<f32>().unwrap()))
+            .collect();
+        assert!(coordinates.iter().all(|x| *x == 0.0 || *x == 10.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_render_flat() {
+        render(&Map::empty((2, 2)), 10.0);
+    }
+}
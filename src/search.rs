@@ -1,61 +1,466 @@
+use crate::graph::Graph;
 use crate::map::Map;
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt::Debug;
 
-// Reference: https://www.redblobgames.com/pathfinding/a-star/implementation.html
-pub fn breadth_first(map: &Map, from: (usize, usize), to: (usize, usize)) -> Vec<(usize, usize)> {
-    // The cells that still need to be processed
-    let mut frontier = vec![];
-    frontier.push(from);
+/// Finds the path that passes through the fewest cells from `from` to `to`, and
+/// returns it backwards: starting at `to` and ending at `from`. Nothing is printed
+/// (see `print_path`).
+///
+/// Reference: `https://www.redblobgames.com/pathfinding/a-star/implementation.html`
+pub fn breadth_first<G: Graph>(
+    map: &G,
+    from: G::Node,
+    to: G::Node,
+) -> Result<Vec<G::Node>, SolveError<G::Node>> {
+    validate(map, from, to)?;
+
+    // The cells that still need to be processed, in the order they were discovered
+    let mut frontier = VecDeque::new();
+    frontier.push_back(from);
 
     // A map that tells us which cell a given cell "came from" during traversal
     let mut came_from = HashMap::new();
     came_from.insert(from, from);
 
-    loop {
-        if let Some(current_indices) = frontier.pop() {
-            // Get this cell's neighbors
-            //let neighbors = map.get_neighbor_indices(current_indices.0, current_indices.1);
-
-            // TODO: this should be handled in the cell struct or something
-            let mut neighbors = vec![];
-            let cell = map.get_cell(current_indices.0, current_indices.1);
-            if cell.n {
-                neighbors.push((current_indices.0 - 1, current_indices.1 + 0));
-            }
-            if cell.s {
-                neighbors.push((current_indices.0 + 1, current_indices.1 + 0));
-            }
-            if cell.e {
-                neighbors.push((current_indices.0 + 0, current_indices.1 + 1));
-            }
-            if cell.w {
-                neighbors.push((current_indices.0 + 0, current_indices.1 - 1));
-            }
+    while let Some(current_indices) = frontier.pop_front() {
+        // Get the neighbors that we can travel to from this cell
+        let neighbors = map.get_open_neighbors(current_indices);
 
-            for neighbor_indices in neighbors.iter() {
-                // If this neighbor hasn't already been visited
-                if !came_from.contains_key(neighbor_indices) {
-                    frontier.push(*neighbor_indices);
-                    came_from.insert(*neighbor_indices, current_indices);
-                }
+        for neighbor_indices in neighbors.iter() {
+            // If this neighbor hasn't already been visited
+            if !came_from.contains_key(neighbor_indices) {
+                frontier.push_back(*neighbor_indices);
+                came_from.insert(*neighbor_indices, current_indices);
             }
-        } else {
-            // The frontier is empty
-            break;
         }
     }
 
+    if !came_from.contains_key(&to) {
+        return Err(SolveError::Unreachable);
+    }
+
     // Reconstruct the path from `from` to `to` by indexing into the map data structure
     let mut current_indices = to;
     let mut path = vec![];
 
     while current_indices != from {
-        println!("{:?}", current_indices);
         path.push(current_indices);
         current_indices = came_from[&current_indices];
     }
     path.push(from);
-    println!("{:?}", from);
 
-    path
+    Ok(path)
+}
+
+/// Prints each cell of `path` on its own line.
+pub fn print_path<N: Debug>(path: &[N]) {
+    for cell in path.iter() {
+        println!("{:?}", cell);
+    }
+}
+
+/// A path through a maze, as found by a `Solver`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution<N> {
+    // The cells along the path, from the start to the goal (inclusive)
+    pub path: Vec<N>,
+
+    // The total cost of the passages along the path
+    pub cost: usize,
+
+    // The number of cells whose neighbors were examined before the goal was reached
+    pub expanded: usize,
+}
+
+/// The reason that a path couldn't be found.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SolveError<N = (usize, usize)> {
+    // No open passages lead from the start to the goal (for example, because the
+    // maze is only partially generated)
+    Unreachable,
+
+    // The specified cell isn't part of the maze (it lies outside of the map, or
+    // outside of its mask)
+    OutOfBounds(N),
+
+    // The start and the goal are the same cell, so there is nothing to solve
+    StartEqualsGoal,
+}
+
+impl<N: Debug> std::fmt::Display for SolveError<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Unreachable => write!(f, "The goal can't be reached from the start"),
+            SolveError::OutOfBounds(cell) => write!(f, "Cell {:?} isn't part of the maze", cell),
+            SolveError::StartEqualsGoal => write!(f, "The start and the goal are the same cell"),
+        }
+    }
+}
+
+impl<N: Debug> std::error::Error for SolveError<N> {}
+
+/// Checks that a path could lead from `from` to `to` before searching for one.
+fn validate<G: Graph>(map: &G, from: G::Node, to: G::Node) -> Result<(), SolveError<G::Node>> {
+    for cell in [from, to].iter() {
+        if !map.contains(*cell) {
+            return Err(SolveError::OutOfBounds(*cell));
+        }
+    }
+    if from == to {
+        return Err(SolveError::StartEqualsGoal);
+    }
+
+    Ok(())
+}
+
+/// A strategy for finding a path between two cells of a maze.
+pub trait Solver<G: Graph = Map> {
+    /// Finds a path from `from` to `to` that only passes through open passages. This
+    /// never panics: invalid or unreachable cells are reported as errors instead.
+    fn solve(
+        &self,
+        map: &G,
+        from: G::Node,
+        to: G::Node,
+    ) -> Result<Solution<G::Node>, SolveError<G::Node>>;
+}
+
+/// Breadth-first search
+pub struct BreadthFirst {}
+
+impl<G: Graph> Solver<G> for BreadthFirst {
+    /// Explores the maze in rings of increasing distance from the start, which
+    /// finds the path that passes through the fewest cells.
+    fn solve(
+        &self,
+        map: &G,
+        from: G::Node,
+        to: G::Node,
+    ) -> Result<Solution<G::Node>, SolveError<G::Node>> {
+        search(map, from, to, Order::FirstIn)
+    }
+}
+
+/// Depth-first search
+pub struct DepthFirst {}
+
+impl<G: Graph> Solver<G> for DepthFirst {
+    /// Follows each passage as far as it goes before backing up to try the next
+    /// one. In a perfect maze this finds the only path there is, but in a maze
+    /// with loops the path may be far from the shortest.
+    fn solve(
+        &self,
+        map: &G,
+        from: G::Node,
+        to: G::Node,
+    ) -> Result<Solution<G::Node>, SolveError<G::Node>> {
+        search(map, from, to, Order::LastIn)
+    }
+}
+
+/// Dijkstra's algorithm
+pub struct Dijkstra {}
+
+impl<G: Graph> Solver<G> for Dijkstra {
+    /// Always expands the cell that is cheapest to reach from the start, which
+    /// finds the cheapest path even when some passages cost more than others.
+    ///
+    /// Reference: `https://en.wikipedia.org/wiki/Dijkstra%27s_algorithm`
+    fn solve(
+        &self,
+        map: &G,
+        from: G::Node,
+        to: G::Node,
+    ) -> Result<Solution<G::Node>, SolveError<G::Node>> {
+        search(map, from, to, Order::Cheapest)
+    }
+}
+
+/// A* search
+pub struct AStar {}
+
+impl<G: Graph> Solver<G> for AStar {
+    /// Like `Dijkstra`, but adds an estimate of the remaining cost to the goal
+    /// (the Manhattan distance, on square maps) when choosing which cell to
+    /// expand next. The path is just as cheap, but far fewer cells are expanded
+    /// along the way.
+    ///
+    /// Reference: `https://en.wikipedia.org/wiki/A*_search_algorithm`
+    fn solve(
+        &self,
+        map: &G,
+        from: G::Node,
+        to: G::Node,
+    ) -> Result<Solution<G::Node>, SolveError<G::Node>> {
+        search(map, from, to, Order::CheapestEstimate)
+    }
+}
+
+/// The order in which `explore` expands the cells it has discovered.
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum Order {
+    // The cell that was discovered first (a queue)
+    FirstIn,
+
+    // The cell that was discovered last (a stack)
+    LastIn,
+
+    // The cell that is cheapest to reach from the start
+    Cheapest,
+
+    // The cell with the cheapest estimated path through it to the goal
+    CheapestEstimate,
+}
+
+/// Searches for a path from `from` to `to`, expanding cells in the specified `order`.
+fn search<G: Graph>(
+    map: &G,
+    from: G::Node,
+    to: G::Node,
+    order: Order,
+) -> Result<Solution<G::Node>, SolveError<G::Node>> {
+    validate(map, from, to)?;
+
+    let (came_from, expanded) = explore(map, from, Some(to), order);
+    if !came_from.contains_key(&to) {
+        return Err(SolveError::Unreachable);
+    }
+
+    // Walk back from the goal to the start
+    let mut path = vec![to];
+    let mut current = to;
+    while current != from {
+        current = came_from[&current].0;
+        path.push(current);
+    }
+    path.reverse();
+
+    Ok(Solution {
+        path,
+        cost: came_from[&to].1,
+        expanded,
+    })
+}
+
+/// For each cell that a search has expanded: the cell it was reached from, and
+/// the cost of reaching it.
+pub(crate) type CameFrom<N> = HashMap<N, (N, usize)>;
+
+/// Expands the cells that can be reached from `from`, in the specified `order`,
+/// until `goal` is expanded (or, if there is no goal, until every reachable cell
+/// is). Returns the cell that each expanded cell was reached from along with the
+/// cost of reaching it, and the number of cells that were expanded.
+///
+/// Each cell is expanded at most once, the first time it leaves the frontier, so
+/// the frontier may hold the same cell more than once (by way of different parents).
+pub(crate) fn explore<G: Graph>(
+    map: &G,
+    from: G::Node,
+    goal: Option<G::Node>,
+    order: Order,
+) -> (CameFrom<G::Node>, usize) {
+    // Each entry is (cell, the cell it was reached from, the cost to reach it). In
+    // the heap, entries are ordered by their priority first, then by the cost to
+    // reach them (highest first), since on a tie the cell that is further along
+    // is more likely to lead straight to the goal.
+    let mut list = VecDeque::new();
+    let mut heap = BinaryHeap::new();
+    match order {
+        Order::FirstIn | Order::LastIn => list.push_back((from, from, 0)),
+        _ => heap.push(Reverse((0, Reverse(0), from, from))),
+    }
+
+    // The cell that each expanded cell was reached from, and how much it cost
+    let mut came_from: CameFrom<G::Node> = HashMap::new();
+    let mut expanded = 0;
+
+    loop {
+        let next = match order {
+            Order::FirstIn => list.pop_front(),
+            Order::LastIn => list.pop_back(),
+            _ => heap
+                .pop()
+                .map(|Reverse((_, Reverse(cost), current, parent))| (current, parent, cost)),
+        };
+        let (current, parent, cost) = match next {
+            Some(next) => next,
+            None => break,
+        };
+
+        if came_from.contains_key(&current) {
+            continue;
+        }
+        came_from.insert(current, (parent, cost));
+        expanded += 1;
+
+        if Some(current) == goal {
+            break;
+        }
+
+        for neighbor in map.get_open_neighbors(current) {
+            if came_from.contains_key(&neighbor) {
+                continue;
+            }
+
+            let cost = cost + map.get_cost(current, neighbor);
+            match order {
+                Order::FirstIn | Order::LastIn => list.push_back((neighbor, current, cost)),
+                Order::Cheapest => heap.push(Reverse((cost, Reverse(cost), neighbor, current))),
+                Order::CheapestEstimate => {
+                    let estimate = match goal {
+                        Some(goal) => cost + map.estimate_cost(neighbor, goal),
+                        None => cost,
+                    };
+                    heap.push(Reverse((estimate, Reverse(cost), neighbor, current)));
+                }
+            }
+        }
+    }
+
+    (came_from, expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::braid::braid;
+    use crate::generators::{Backtracking, Generator, Kruskal};
+    use crate::mask::Mask;
+
+    fn solvers() -> Vec<Box<dyn Solver>> {
+        vec![
+            Box::new(BreadthFirst {}),
+            Box::new(DepthFirst {}),
+            Box::new(Dijkstra {}),
+            Box::new(AStar {}),
+        ]
+    }
+
+    #[test]
+    fn test_breadth_first_order() {
+        // In an open room, breadth-first search finds a shortest path
+        let mut map = Map::empty((5, 5));
+        map.open_all_paths();
+
+        assert_eq!(breadth_first(&map, (0, 0), (4, 4)).unwrap().len(), 9);
+    }
+
+    #[test]
+    fn test_breadth_first_errors() {
+        let mut map = Map::empty((3, 3));
+        map.set_mask(Some(Mask::from_ascii("...\n.X.\n...")));
+
+        assert_eq!(
+            breadth_first(&map, (0, 0), (2, 2)),
+            Err(SolveError::Unreachable)
+        );
+        assert_eq!(
+            breadth_first(&map, (0, 0), (1, 1)),
+            Err(SolveError::OutOfBounds((1, 1)))
+        );
+        assert_eq!(
+            breadth_first(&map, (5, 0), (0, 0)),
+            Err(SolveError::OutOfBounds((5, 0)))
+        );
+        assert_eq!(
+            breadth_first(&map, (2, 2), (2, 2)),
+            Err(SolveError::StartEqualsGoal)
+        );
+    }
+
+    #[test]
+    fn test_perfect_maze() {
+        let mut map = Map::empty((12, 12));
+        Backtracking {}.build(&mut map, &mut rand::thread_rng());
+
+        // There is only one path through a perfect maze, so every solver finds it
+        let expected = BreadthFirst {}.solve(&map, (0, 0), (11, 11)).unwrap();
+        for solver in solvers().iter() {
+            let solution = solver.solve(&map, (0, 0), (11, 11)).unwrap();
+
+            assert_eq!(solution.path, expected.path);
+            assert_eq!(solution.cost, solution.path.len() - 1);
+            assert!(solution.expanded <= map.get_cell_count());
+        }
+    }
+
+    #[test]
+    fn test_open_room() {
+        let mut map = Map::empty((10, 10));
+        map.open_all_paths();
+
+        let shortest = 18;
+        for solver in solvers().iter() {
+            let solution = solver.solve(&map, (0, 0), (9, 9)).unwrap();
+
+            assert_eq!(solution.path.first(), Some(&(0, 0)));
+            assert_eq!(solution.path.last(), Some(&(9, 9)));
+            assert_eq!(solution.cost, solution.path.len() - 1);
+            assert!(solution.cost >= shortest);
+        }
+
+        // The optimal solvers agree on the cost, but A* looks at far fewer cells
+        let dijkstra = Dijkstra {}.solve(&map, (0, 0), (9, 9)).unwrap();
+        let a_star = AStar {}.solve(&map, (0, 0), (9, 9)).unwrap();
+        assert_eq!(dijkstra.cost, shortest);
+        assert_eq!(a_star.cost, shortest);
+        assert!(a_star.expanded < dijkstra.expanded);
+    }
+
+    #[test]
+    fn test_braided_maze() {
+        let mut rng = rand::thread_rng();
+        let mut map = Map::empty((15, 15));
+        Kruskal {}.build(&mut map, &mut rng);
+        braid(&mut map, 1.0, &mut rng);
+
+        let shortest = BreadthFirst {}.solve(&map, (0, 0), (14, 14)).unwrap();
+        for solver in [&Dijkstra {} as &dyn Solver, &AStar {}].iter() {
+            assert_eq!(
+                solver.solve(&map, (0, 0), (14, 14)).unwrap().cost,
+                shortest.cost
+            );
+        }
+        assert!(DepthFirst {}.solve(&map, (0, 0), (14, 14)).unwrap().cost >= shortest.cost);
+    }
+
+    #[test]
+    fn test_tunnel_cost() {
+        // Two corridors cross in the middle of a plus sign: the east-west one
+        // tunnels underneath the center cell, which costs two steps
+        let mut map = Map::empty((3, 3));
+        map.set_weave(true);
+        map.visit(1, 1);
+        map.open_path_between((0, 1), (1, 1));
+        map.open_path_between((1, 1), (2, 1));
+        map.open_path_between((1, 0), (1, 2));
+
+        for solver in solvers().iter() {
+            let solution = solver.solve(&map, (1, 0), (1, 2)).unwrap();
+            assert_eq!(solution.path, vec![(1, 0), (1, 2)]);
+            assert_eq!(solution.cost, 2);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let map = Map::empty((3, 3));
+
+        for solver in solvers().iter() {
+            assert_eq!(
+                solver.solve(&map, (0, 0), (2, 2)),
+                Err(SolveError::Unreachable)
+            );
+            assert_eq!(
+                solver.solve(&map, (0, 0), (0, 3)),
+                Err(SolveError::OutOfBounds((0, 3)))
+            );
+            assert_eq!(
+                solver.solve(&map, (1, 1), (1, 1)),
+                Err(SolveError::StartEqualsGoal)
+            );
+        }
+    }
 }
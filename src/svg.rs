@@ -0,0 +1,852 @@
+use crate::graph::Graph;
+use crate::map::Map;
+use crate::topology::{Direction, Topology};
+use crate::voronoi::VoronoiMaze;
+use std::f32::consts::PI;
+use std::fmt::Write;
+
+// References:
+// https://www.redblobgames.com/grids/hexagons/
+// http://weblog.jamisbuck.org/2011/2/7/maze-generation-algorithm-recap
+
+/// A point in the SVG drawing.
+type Point = (f32, f32);
+
+/// The margin (in units) around the maze.
+const MARGIN: f32 = 4.0;
+
+/// The position (column, row) of each face of a cube in its unfolded net, measured
+/// in faces: the top and bottom faces sit above and below the front face, and the
+/// remaining faces run to the right of the left face in a single strip.
+const CUBE_NET: [(usize, usize); 6] = [(1, 1), (2, 1), (3, 1), (0, 1), (1, 0), (1, 2)];
+
+/// The shape of one side of a cell.
+enum Side {
+    // A straight line between two points
+    Line(Point, Point),
+
+    // An arc of a circle centered on `center`, running clockwise between two angles
+    // (in radians, where zero points right)
+    Arc {
+        center: Point,
+        radius: f32,
+        from: f32,
+        to: f32,
+    },
+}
+
+/// Returns the sides of cell <`i`, `j`> on a map with the specified `topology` and
+/// `dimensions`, where each cell is roughly `size` units across: one side for each
+/// direction that a passage can lead out of the cell.
+fn get_sides(
+    topology: Topology,
+    dimensions: (usize, usize),
+    i: usize,
+    j: usize,
+    size: f32,
+) -> Vec<(Direction, Side)> {
+    match topology {
+        Topology::Square | Topology::Levels(_) | Topology::Wrapped(_) | Topology::Cube => {
+            let (x, y) = get_square_corner(topology, dimensions, i, j, size);
+            let nw = (x, y);
+            let ne = (x + size, y);
+            let sw = (x, y + size);
+            let se = (x + size, y + size);
+
+            vec![
+                (Direction::North, Side::Line(nw, ne)),
+                (Direction::South, Side::Line(sw, se)),
+                (Direction::West, Side::Line(nw, sw)),
+                (Direction::East, Side::Line(ne, se)),
+            ]
+        }
+        Topology::Hex => {
+            // `size` is the distance between two opposite sides of a hexagon
+            let radius = size / 3f32.sqrt();
+            let cx = size * (j as f32 + 0.5 * (i % 2) as f32) + size * 0.5;
+            let cy = 1.5 * radius * i as f32 + radius;
+
+            let top = (cx, cy - radius);
+            let upper_right = (cx + size * 0.5, cy - radius * 0.5);
+            let lower_right = (cx + size * 0.5, cy + radius * 0.5);
+            let bottom = (cx, cy + radius);
+            let lower_left = (cx - size * 0.5, cy + radius * 0.5);
+            let upper_left = (cx - size * 0.5, cy - radius * 0.5);
+
+            vec![
+                (Direction::NorthWest, Side::Line(upper_left, top)),
+                (Direction::NorthEast, Side::Line(top, upper_right)),
+                (Direction::West, Side::Line(lower_left, upper_left)),
+                (Direction::East, Side::Line(upper_right, lower_right)),
+                (Direction::SouthWest, Side::Line(bottom, lower_left)),
+                (Direction::SouthEast, Side::Line(lower_right, bottom)),
+            ]
+        }
+        Topology::Polar => {
+            // Ring `i` spans the radii between `i + 1` and `i + 2` cells from the
+            // center, and the first cell of each ring starts at the top
+            let center = get_polar_center(dimensions, size);
+            let inner = (i + 1) as f32 * size;
+            let outer = inner + size;
+
+            let width = topology.get_row_width(dimensions, i);
+            let step = 2.0 * PI / width as f32;
+            let from = j as f32 * step - 0.5 * PI;
+            let to = from + step;
+
+            let radial = |angle: f32| {
+                let (sin, cos) = angle.sin_cos();
+                Side::Line(
+                    (center.0 + inner * cos, center.1 + inner * sin),
+                    (center.0 + outer * cos, center.1 + outer * sin),
+                )
+            };
+            let arc = |radius: f32, from: f32, to: f32| Side::Arc {
+                center,
+                radius,
+                from,
+                to,
+            };
+
+            let mut sides = vec![
+                (Direction::Inward, arc(inner, from, to)),
+                (Direction::Clockwise, radial(to)),
+                (Direction::CounterClockwise, radial(from)),
+            ];
+
+            // The outer side is split in two if it borders two cells in the next ring
+            if topology
+                .get_directions(dimensions, i, j)
+                .contains(&Direction::OutwardClockwise)
+            {
+                let middle = from + 0.5 * step;
+                sides.push((Direction::Outward, arc(outer, from, middle)));
+                sides.push((Direction::OutwardClockwise, arc(outer, middle, to)));
+            } else {
+                sides.push((Direction::Outward, arc(outer, from, to)));
+            }
+            sides
+        }
+        Topology::Triangle => {
+            // `size` is the length of each side, and neighboring triangles in a row
+            // overlap by half of that
+            let height = size * 3f32.sqrt() * 0.5;
+            let (x, y) = (j as f32 * size * 0.5, i as f32 * height);
+
+            if Topology::is_upright(i, j) {
+                let apex = (x + size * 0.5, y);
+                let left = (x, y + height);
+                let right = (x + size, y + height);
+
+                vec![
+                    (Direction::West, Side::Line(left, apex)),
+                    (Direction::East, Side::Line(apex, right)),
+                    (Direction::South, Side::Line(left, right)),
+                ]
+            } else {
+                let left = (x, y);
+                let right = (x + size, y);
+                let apex = (x + size * 0.5, y + height);
+
+                vec![
+                    (Direction::North, Side::Line(left, right)),
+                    (Direction::West, Side::Line(left, apex)),
+                    (Direction::East, Side::Line(right, apex)),
+                ]
+            }
+        }
+        Topology::Upsilon => {
+            // `size` is the width of an octagon, and the squares fill the gaps
+            // between them, so neighboring cells are `size / sqrt(2)` apart
+            let pitch = size / 2f32.sqrt();
+            let center = (size * 0.5 + j as f32 * pitch, size * 0.5 + i as f32 * pitch);
+            let straight = [
+                (Direction::North, -0.5 * PI),
+                (Direction::South, 0.5 * PI),
+                (Direction::West, PI),
+                (Direction::East, 0.0),
+            ];
+
+            if Topology::is_octagon(i, j) {
+                let diagonal = [
+                    (Direction::NorthWest, -0.75 * PI),
+                    (Direction::NorthEast, -0.25 * PI),
+                    (Direction::SouthWest, 0.75 * PI),
+                    (Direction::SouthEast, 0.25 * PI),
+                ];
+                let normals: Vec<_> = straight.iter().chain(diagonal.iter()).cloned().collect();
+                get_regular_sides(center, size * 0.5, &normals, PI / 8.0)
+            } else {
+                get_regular_sides(center, pitch - size * 0.5, &straight, PI / 4.0)
+            }
+        }
+        Topology::Sigma => {
+            // `size` is the distance between the top and bottom of a hexagon
+            let radius = size / 3f32.sqrt();
+            let center = (
+                radius + 1.5 * radius * j as f32,
+                size * (i as f32 + 0.5 + 0.5 * (j % 2) as f32),
+            );
+            let normals = [
+                (Direction::North, -0.5 * PI),
+                (Direction::NorthEast, -PI / 6.0),
+                (Direction::SouthEast, PI / 6.0),
+                (Direction::South, 0.5 * PI),
+                (Direction::SouthWest, 5.0 * PI / 6.0),
+                (Direction::NorthWest, -5.0 * PI / 6.0),
+            ];
+
+            get_regular_sides(center, size * 0.5, &normals, PI / 6.0)
+        }
+    }
+}
+
+/// Returns the sides of a regular polygon around `center`, whose sides are all
+/// `apothem` units from the center. Each side is centered on the angle (in radians,
+/// where zero points right) paired with its direction in `normals`, and spans
+/// `half_angle` radians to either side of it.
+fn get_regular_sides(
+    center: Point,
+    apothem: f32,
+    normals: &[(Direction, f32)],
+    half_angle: f32,
+) -> Vec<(Direction, Side)> {
+    let radius = apothem / half_angle.cos();
+    let corner = |angle: f32| {
+        let (sin, cos) = angle.sin_cos();
+        (center.0 + radius * cos, center.1 + radius * sin)
+    };
+
+    normals
+        .iter()
+        .map(|(direction, angle)| {
+            let side = Side::Line(corner(angle - half_angle), corner(angle + half_angle));
+            (*direction, side)
+        })
+        .collect()
+}
+
+/// Returns the top-left corner of square cell <`i`, `j`>. The levels of a multi-level
+/// map are drawn side by side, from left to right, one cell apart, and the faces of
+/// a cube are unfolded into a cross.
+fn get_square_corner(
+    topology: Topology,
+    dimensions: (usize, usize),
+    i: usize,
+    j: usize,
+    size: f32,
+) -> Point {
+    if let Topology::Cube = topology {
+        let n = dimensions.1;
+        let (col, row) = CUBE_NET[i / n];
+        return ((col * n + j) as f32 * size, (row * n + i % n) as f32 * size);
+    }
+
+    let rows = topology.get_rows_per_level(dimensions);
+    let (level, row) = (i / rows, i % rows);
+
+    (
+        (level * (dimensions.1 + 1) + j) as f32 * size,
+        row as f32 * size,
+    )
+}
+
+/// Writes a small arrow to `svg` in the middle of the square cell whose top-left
+/// corner is `corner`, marking stairs that lead up (pointing up, on the left) or
+/// down (pointing down, on the right).
+fn write_stairs(
+    svg: &mut String,
+    corner: Point,
+    direction: Direction,
+    size: f32,
+) -> std::fmt::Result {
+    let (x, y) = (corner.0 + size * 0.5, corner.1 + size * 0.5);
+    let (dx, dy) = (size * 0.15, size * 0.1);
+
+    let (x, tip) = match direction {
+        Direction::Up => (x - dx, -dy),
+        _ => (x + dx, dy),
+    };
+
+    writeln!(
+        svg,
+        r#"<polyline points="{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}" fill="none"/>"#,
+        x - dx,
+        y - tip,
+        x,
+        y + tip,
+        x + dx,
+        y - tip
+    )
+}
+
+/// Writes a small arrow to `svg` that points out of the square cell whose top-left
+/// corner is `corner`, through the middle of its side from `a` to `b`. This marks
+/// passages that wrap around to the opposite edge of the map, or that lead onto a
+/// face of a cube which isn't drawn next to the current one.
+fn write_exit(svg: &mut String, corner: Point, a: Point, b: Point, size: f32) -> std::fmt::Result {
+    let center = (corner.0 + size * 0.5, corner.1 + size * 0.5);
+    let tip = ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5);
+
+    // The arrow's barbs are swept back towards the center of the cell
+    let back = ((center.0 - tip.0) * 0.3, (center.1 - tip.1) * 0.3);
+    let across = (back.1, -back.0);
+
+    writeln!(
+        svg,
+        r#"<polyline points="{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}" fill="none"/>"#,
+        tip.0 + back.0 + across.0,
+        tip.1 + back.1 + across.1,
+        tip.0,
+        tip.1,
+        tip.0 + back.0 - across.0,
+        tip.1 + back.1 - across.1
+    )
+}
+
+/// Writes the walls of square cell <`i`, `j`> of a weave maze to `svg`. Each cell is
+/// drawn inset from its bounds, so that passages become corridors with walls on
+/// either side, and tunnels show up as short stubs that disappear underneath the
+/// corridor of the cell above them.
+fn write_inset_cell(
+    svg: &mut String,
+    map: &Map,
+    i: usize,
+    j: usize,
+    size: f32,
+) -> std::fmt::Result {
+    let cell = map.get_cell(i, j);
+    let inset = size * 0.2;
+
+    let (x0, y0) = (j as f32 * size, i as f32 * size);
+    let (x1, y1) = (x0 + inset, y0 + inset);
+    let (x2, y2) = (x0 + size - inset, y0 + size - inset);
+    let (x3, y3) = (x0 + size, y0 + size);
+
+    // For each direction: the inner edge of the cell, and the two walls of a corridor
+    // leading out of the cell in that direction
+    let sides = [
+        (
+            Direction::North,
+            ((x1, y1), (x2, y1)),
+            [((x1, y0), (x1, y1)), ((x2, y0), (x2, y1))],
+        ),
+        (
+            Direction::South,
+            ((x1, y2), (x2, y2)),
+            [((x1, y2), (x1, y3)), ((x2, y2), (x2, y3))],
+        ),
+        (
+            Direction::West,
+            ((x1, y1), (x1, y2)),
+            [((x0, y1), (x1, y1)), ((x0, y2), (x1, y2))],
+        ),
+        (
+            Direction::East,
+            ((x2, y1), (x2, y2)),
+            [((x2, y1), (x3, y1)), ((x2, y2), (x3, y2))],
+        ),
+    ];
+
+    for (direction, edge, corridor) in sides.iter() {
+        // A tunnel enters the cell wherever its own corridor doesn't
+        let tunnel = cell.has_tunnel() && !cell.is_open(*direction);
+
+        if cell.is_open(*direction) || tunnel {
+            for (a, b) in corridor.iter() {
+                write_side(svg, &Side::Line(*a, *b))?;
+            }
+        }
+        if !cell.is_open(*direction) {
+            write_side(svg, &Side::Line(edge.0, edge.1))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the center of the drawing of a polar map with the specified `dimensions`.
+fn get_polar_center(dimensions: (usize, usize), size: f32) -> Point {
+    let radius = (dimensions.0 + 1) as f32 * size;
+    (radius, radius)
+}
+
+/// Writes `side` to `svg` as an SVG element.
+fn write_side(svg: &mut String, side: &Side) -> std::fmt::Result {
+    match *side {
+        Side::Line(a, b) => writeln!(
+            svg,
+            r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}"/>"#,
+            a.0, a.1, b.0, b.1
+        ),
+        Side::Arc {
+            center,
+            radius,
+            from,
+            to,
+        } => {
+            // An arc whose ends meet can't be drawn as a single path, so split
+            // anything longer than half a turn into two pieces
+            if to - from > PI {
+                let middle = 0.5 * (from + to);
+                write_side(
+                    svg,
+                    &Side::Arc {
+                        center,
+                        radius,
+                        from,
+                        to: middle,
+                    },
+                )?;
+                return write_side(
+                    svg,
+                    &Side::Arc {
+                        center,
+                        radius,
+                        from: middle,
+                        to,
+                    },
+                );
+            }
+
+            let (from_sin, from_cos) = from.sin_cos();
+            let (to_sin, to_cos) = to.sin_cos();
+            writeln!(
+                svg,
+                r#"<path d="M {:.2} {:.2} A {r:.2} {r:.2} 0 0 1 {:.2} {:.2}" fill="none"/>"#,
+                center.0 + radius * from_cos,
+                center.1 + radius * from_sin,
+                center.0 + radius * to_cos,
+                center.1 + radius * to_sin,
+                r = radius
+            )
+        }
+    }
+}
+
+/// Returns the points along `side`, from one end to the other. Arcs are traced
+/// with a handful of straight segments.
+fn get_points(side: &Side) -> Vec<Point> {
+    match *side {
+        Side::Line(a, b) => vec![a, b],
+        Side::Arc {
+            center,
+            radius,
+            from,
+            to,
+        } => {
+            let segments = 8;
+            (0..=segments)
+                .map(|k| {
+                    let angle = from + (to - from) * k as f32 / segments as f32;
+                    let (sin, cos) = angle.sin_cos();
+                    (center.0 + radius * cos, center.1 + radius * sin)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Returns the outline of a cell with the specified `sides`, as a list of points
+/// that runs once around the cell. The sides may come in any order, and may each
+/// run in either direction, so they are chained together end to end.
+fn get_outline(sides: &[(Direction, Side)]) -> Vec<Point> {
+    let mut pieces: Vec<Vec<Point>> = sides.iter().map(|(_, side)| get_points(side)).collect();
+    let near = |a: Point, b: Point| (a.0 - b.0).abs() + (a.1 - b.1).abs() < 0.01;
+
+    let mut outline = pieces.remove(0);
+    while !pieces.is_empty() {
+        let last = outline[outline.len() - 1];
+        let next = pieces
+            .iter()
+            .position(|piece| near(piece[0], last) || near(piece[piece.len() - 1], last));
+
+        let mut piece = match next {
+            Some(next) => pieces.remove(next),
+            None => break,
+        };
+        if !near(piece[0], last) {
+            piece.reverse();
+        }
+        outline.extend(piece.into_iter().skip(1));
+    }
+
+    outline
+}
+
+/// Writes the interior of cell <`i`, `j`> to `svg`, filled with `color`.
+fn write_fill(
+    svg: &mut String,
+    map: &Map,
+    i: usize,
+    j: usize,
+    size: f32,
+    color: (u8, u8, u8),
+) -> std::fmt::Result {
+    let sides = get_sides(map.get_topology(), map.get_dimensions(), i, j, size);
+    let points: Vec<String> = get_outline(&sides)
+        .iter()
+        .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+        .collect();
+
+    writeln!(
+        svg,
+        r#"<polygon points="{}" fill="rgb({},{},{})" stroke="none"/>"#,
+        points.join(" "),
+        color.0,
+        color.1,
+        color.2
+    )
+}
+
+/// Returns the size (width, height) of the drawing of a map with the specified
+/// `topology` and `dimensions`, excluding the margin.
+fn get_extent(topology: Topology, dimensions: (usize, usize), size: f32) -> Point {
+    let (rows, cols) = (dimensions.0 as f32, dimensions.1 as f32);
+
+    match topology {
+        Topology::Square | Topology::Wrapped(_) => (cols * size, rows * size),
+        Topology::Hex => {
+            let radius = size / 3f32.sqrt();
+            (size * (cols + 0.5), radius * (1.5 * rows + 0.5))
+        }
+        Topology::Polar => {
+            let center = get_polar_center(dimensions, size);
+            (2.0 * center.0, 2.0 * center.1)
+        }
+        Topology::Triangle => (size * 0.5 * (cols + 1.0), size * 3f32.sqrt() * 0.5 * rows),
+        Topology::Levels(levels) => {
+            let levels = levels as f32;
+            let rows = topology.get_rows_per_level(dimensions) as f32;
+            (size * (levels * (cols + 1.0) - 1.0), rows * size)
+        }
+        Topology::Cube => (4.0 * cols * size, 3.0 * cols * size),
+        Topology::Upsilon => {
+            let pitch = size / 2f32.sqrt();
+            ((cols - 1.0) * pitch + size, (rows - 1.0) * pitch + size)
+        }
+        Topology::Sigma => {
+            let radius = size / 3f32.sqrt();
+            let shifted = if dimensions.1 > 1 { 0.5 } else { 0.0 };
+            (radius * (1.5 * cols + 0.5), size * (rows + shifted))
+        }
+    }
+}
+
+/// Writes the opening of an SVG document to `svg`, for a drawing of the specified
+/// `extent` (excluding the margin) whose cells are roughly `size` units across.
+fn write_header(svg: &mut String, extent: Point, size: f32) -> std::fmt::Result {
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = extent.0 + 2.0 * MARGIN,
+        h = extent.1 + 2.0 * MARGIN
+    )?;
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+    writeln!(
+        svg,
+        r#"<g transform="translate({m} {m})" stroke="black" stroke-width="{s}" stroke-linecap="round">"#,
+        m = MARGIN,
+        s = (size / 10.0).max(1.0)
+    )
+}
+
+/// Renders `map` as an SVG document, where each cell is roughly `size` units across.
+/// A wall is drawn along every side of a cell that doesn't lead to an open passage.
+pub fn render(map: &Map, size: f32) -> String {
+    render_filled(map, size, &|_| None)
+}
+
+/// Renders `map` as an SVG document, like `render`, but first fills in the background
+/// of each cell with the color returned by `fill` (if any), which makes it possible
+/// to draw heatmaps and highlight regions of the maze.
+pub fn render_filled(
+    map: &Map,
+    size: f32,
+    fill: &dyn Fn((usize, usize)) -> Option<(u8, u8, u8)>,
+) -> String {
+    let topology = map.get_topology();
+    let dimensions = map.get_dimensions();
+
+    let mut svg = String::new();
+    write_header(&mut svg, get_extent(topology, dimensions, size), size).unwrap();
+
+    // Fill every cell before drawing any walls, so that the walls end up on top
+    for (i, j) in map.get_all_grid_indices() {
+        if let Some(color) = fill((i, j)) {
+            write_fill(&mut svg, map, i, j, size, color).unwrap();
+        }
+    }
+
+    for (i, j) in map.get_all_grid_indices() {
+        let cell = map.get_cell(i, j);
+
+        if map.is_weave() {
+            write_inset_cell(&mut svg, map, i, j, size).unwrap();
+            continue;
+        }
+
+        for (direction, side) in get_sides(topology, dimensions, i, j, size) {
+            let neighbor = topology
+                .get_neighbor(dimensions, i, j, direction)
+                .filter(|(ni, nj)| map.contains(*ni, *nj));
+
+            // Shared walls are only drawn once, from the cell that comes first, unless
+            // they wrap around the map and therefore appear along both edges
+            let wraps = match (topology, neighbor) {
+                (Topology::Cube, Some((ni, nj))) => {
+                    let (x, y) = get_square_corner(topology, dimensions, i, j, size);
+                    let (nx, ny) = get_square_corner(topology, dimensions, ni, nj, size);
+                    ((nx - x).abs() + (ny - y).abs() - size).abs() > size * 0.01
+                }
+                _ => topology.wraps(dimensions, i, j, direction),
+            };
+            let owned = match neighbor {
+                Some(neighbor) => wraps || (i, j) < neighbor,
+                None => true,
+            };
+
+            if owned && !cell.is_open(direction) {
+                write_side(&mut svg, &side).unwrap();
+            }
+
+            if let (true, true, Side::Line(a, b)) = (wraps, cell.is_open(direction), &side) {
+                let corner = get_square_corner(topology, dimensions, i, j, size);
+                write_exit(&mut svg, corner, *a, *b, size).unwrap();
+            }
+        }
+
+        if let Topology::Levels(_) = topology {
+            let corner = get_square_corner(topology, dimensions, i, j, size);
+
+            for direction in [Direction::Up, Direction::Down].iter() {
+                if cell.is_open(*direction) {
+                    write_stairs(&mut svg, corner, *direction, size).unwrap();
+                }
+            }
+        }
+    }
+
+    writeln!(svg, "</g>\n</svg>").unwrap();
+    svg
+}
+
+/// Renders a Voronoi maze as an SVG document, at the same scale as its sites. A
+/// wall is drawn along every edge of a cell that doesn't lead to an open passage,
+/// including the edges along the border.
+pub fn render_voronoi(maze: &VoronoiMaze) -> String {
+    let count = maze.get_node_count();
+
+    // Each cell covers roughly the same area, which determines the stroke width
+    let (width, height) = maze.get_size();
+    let size = (width * height / count as f32).sqrt();
+
+    let mut svg = String::new();
+    write_header(&mut svg, (width, height), size).unwrap();
+
+    for k in 0..count {
+        let polygon = maze.get_polygon(k);
+        let open = maze.get_open_neighbors(k);
+
+        for (idx, (a, neighbor)) in polygon.iter().enumerate() {
+            let (b, _) = polygon[(idx + 1) % polygon.len()];
+
+            // Shared walls are only drawn once, from the cell that comes first
+            let drawn = match neighbor {
+                Some(neighbor) => k < *neighbor && !open.contains(neighbor),
+                None => true,
+            };
+
+            if drawn {
+                write_side(&mut svg, &Side::Line(*a, b)).unwrap();
+            }
+        }
+    }
+
+    writeln!(svg, "</g>\n</svg>").unwrap();
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mask::Mask;
+    use crate::topology::Wrap;
+
+    #[test]
+    fn test_render_square() {
+        let mut map = Map::empty((1, 2));
+        map.open_path_between((0, 0), (0, 1));
+
+        // Two cells with a single passage between them have six outer walls
+        let svg = render(&map, 10.0);
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<line").count(), 6);
+    }
+
+    #[test]
+    fn test_render_hex() {
+        let map = Map::with_topology((1, 1), Topology::Hex);
+
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 6);
+    }
+
+    #[test]
+    fn test_render_triangle() {
+        let mut map = Map::with_topology((1, 3), Topology::Triangle);
+        map.open_path_between((0, 0), (0, 1));
+
+        // Three triangles have nine sides, one of which is open and two of which
+        // are shared
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 6);
+    }
+
+    #[test]
+    fn test_render_upsilon() {
+        let mut map = Map::with_topology((2, 2), Topology::Upsilon);
+        map.open_path_between((0, 0), (1, 1));
+        map.open_path_between((0, 0), (0, 1));
+        map.open_path_between((1, 0), (1, 1));
+
+        // Two octagons and two squares share five sides, three of which are open
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 8 + 8 + 4 + 4 - 5 - 3);
+    }
+
+    #[test]
+    fn test_render_sigma() {
+        let mut map = Map::with_topology((1, 2), Topology::Sigma);
+        map.open_path_between((0, 0), (0, 1));
+
+        // The shared side is open, so only the outline of the pair is drawn
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 10);
+    }
+
+    #[test]
+    fn test_render_levels() {
+        let mut map = Map::with_levels((1, 1), 2);
+        map.open_path_between((0, 0), (1, 0));
+
+        // Each level is a single closed cell, with one flight of stairs between them
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 8);
+        assert_eq!(svg.matches("<polyline").count(), 2);
+    }
+
+    #[test]
+    fn test_render_wrapped() {
+        let mut map = Map::with_topology((1, 3), Topology::Wrapped(Wrap::Horizontal));
+        map.open_path_between((0, 2), (0, 0));
+
+        // The passage around the edge is open, so both ends are marked instead of walled
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 8);
+        assert_eq!(svg.matches("<polyline").count(), 2);
+    }
+
+    #[test]
+    fn test_render_cube() {
+        let mut map = Map::with_cube(1);
+        map.open_path_between((0, 0), (1, 0));
+        map.open_path_between((0, 0), (4, 0));
+
+        // The front face is open to the right face, which is drawn beside it, and to
+        // the top face, which is drawn above it. Of the other 10 edges of the cube,
+        // 3 are drawn once (where the faces are next to each other in the net) and
+        // 7 are drawn twice.
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 17);
+        assert_eq!(svg.matches("<polyline").count(), 0);
+
+        // A passage from the front face to the left face runs across the net
+        map.open_path_between((0, 0), (3, 0));
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 16);
+        assert_eq!(svg.matches("<polyline").count(), 0);
+
+        // ...but one from the right face to the top face leaves the net
+        map.open_path_between((1, 0), (4, 0));
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 14);
+        assert_eq!(svg.matches("<polyline").count(), 2);
+    }
+
+    #[test]
+    fn test_render_masked() {
+        let mut map = Map::empty((1, 3));
+        map.set_mask(Some(Mask::from_ascii("..X")));
+
+        // The excluded cell isn't drawn, so the two remaining cells are walled in
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 7);
+    }
+
+    #[test]
+    fn test_render_weave() {
+        let mut map = Map::empty((3, 3));
+        map.set_weave(true);
+        map.open_path_between((1, 1), (0, 1));
+        map.open_path_between((1, 1), (2, 1));
+        map.visit(1, 1);
+        map.open_path_between((1, 0), (1, 2));
+
+        // The middle cell has two corridor walls on each side (four of which belong
+        // to the tunnel), and inner edges where the tunnel runs underneath
+        let mut middle = String::new();
+        write_inset_cell(&mut middle, &map, 1, 1, 10.0).unwrap();
+        assert_eq!(middle.matches("<line").count(), 10);
+
+        // The four dead ends have three inner edges and a corridor, and the corners
+        // are closed off entirely
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 10 + 4 * 5 + 4 * 4);
+    }
+
+    #[test]
+    fn test_render_filled() {
+        let map = Map::empty((2, 3));
+        let svg = render_filled(&map, 10.0, &|(i, _)| Some((i as u8, 0, 0)));
+        assert_eq!(svg.matches("<polygon").count(), 6);
+        assert_eq!(svg.matches("rgb(1,0,0)").count(), 3);
+
+        // The fills come before the walls
+        assert!(svg.rfind("<polygon").unwrap() < svg.find("<line").unwrap());
+
+        // Every kind of cell, including the arcs of a polar maze, has a closed outline
+        for topology in [Topology::Polar, Topology::Hex, Topology::Upsilon].iter() {
+            let map = Map::with_topology((3, 4), *topology);
+            for (i, j) in map.get_all_grid_indices() {
+                let sides = get_sides(*topology, map.get_dimensions(), i, j, 10.0);
+                let outline = get_outline(&sides);
+                let (first, last) = (outline[0], outline[outline.len() - 1]);
+                assert!((first.0 - last.0).abs() + (first.1 - last.1).abs() < 0.01);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_voronoi() {
+        let sites = vec![(1.0, 1.0), (3.0, 1.0), (1.0, 3.0), (3.0, 3.0)];
+        let mut maze = VoronoiMaze::from_sites((4.0, 4.0), sites);
+        maze.open_path_between(0, 1);
+
+        // The border is split into 8 segments, and three of the four inner walls are closed
+        let svg = render_voronoi(&maze);
+        assert_eq!(svg.matches("<line").count(), 8 + 3);
+    }
+
+    #[test]
+    fn test_render_polar() {
+        let mut map = Map::with_topology((2, 4), Topology::Polar);
+        map.open_all_paths();
+
+        // With every passage open, only the innermost and outermost circles remain,
+        // drawn as one arc per cell
+        let svg = render(&map, 10.0);
+        assert_eq!(svg.matches("<line").count(), 0);
+        assert_eq!(svg.matches("<path").count(), 8);
+    }
+}
@@ -0,0 +1,839 @@
+// References:
+// https://www.redblobgames.com/grids/hexagons/
+// https://en.wikipedia.org/wiki/Cube#Orthogonal_projections
+// http://www.astrolog.org/labyrnth/algrithm.htm
+// http://weblog.jamisbuck.org/2011/2/7/maze-generation-algorithm-recap
+
+/// One of the directions in which a passage can lead out of a cell. Which
+/// directions are available depends on the map's `Topology`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+    Inward,
+    Outward,
+    OutwardClockwise,
+    Clockwise,
+    CounterClockwise,
+    Up,
+    Down,
+}
+
+impl Direction {
+    /// Returns the bit that represents this direction in a cell's set of open passages.
+    pub(crate) fn bit(self) -> u16 {
+        1 << (self as u16)
+    }
+
+    /// Returns the direction that points the opposite way, or `None` for the polar
+    /// directions that don't have a unique opposite (e.g. `Outward`).
+    pub fn get_opposite(self) -> Option<Direction> {
+        match self {
+            Direction::North => Some(Direction::South),
+            Direction::South => Some(Direction::North),
+            Direction::East => Some(Direction::West),
+            Direction::West => Some(Direction::East),
+            Direction::NorthEast => Some(Direction::SouthWest),
+            Direction::NorthWest => Some(Direction::SouthEast),
+            Direction::SouthEast => Some(Direction::NorthWest),
+            Direction::SouthWest => Some(Direction::NorthEast),
+            Direction::Clockwise => Some(Direction::CounterClockwise),
+            Direction::CounterClockwise => Some(Direction::Clockwise),
+            Direction::Up => Some(Direction::Down),
+            Direction::Down => Some(Direction::Up),
+            Direction::Inward | Direction::Outward | Direction::OutwardClockwise => None,
+        }
+    }
+}
+
+/// The edges of a square map that lead back around to the opposite edge.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Wrap {
+    // The left and right edges meet (a cylinder)
+    Horizontal,
+
+    // The top and bottom edges meet (a cylinder on its side)
+    Vertical,
+
+    // Both pairs of edges meet (a torus)
+    Both,
+
+    // The left and right edges meet, but upside down (a Möbius strip)
+    Mobius,
+
+    // The left and right edges meet upside down, and the top and bottom edges
+    // meet as usual (a Klein bottle)
+    Klein,
+}
+
+impl Wrap {
+    fn is_horizontal(self) -> bool {
+        self != Wrap::Vertical
+    }
+
+    fn is_vertical(self) -> bool {
+        matches!(self, Wrap::Vertical | Wrap::Both | Wrap::Klein)
+    }
+
+    fn is_flipped(self) -> bool {
+        matches!(self, Wrap::Mobius | Wrap::Klein)
+    }
+}
+
+/// The shape of the cells in a map, and how they are connected to one another.
+/// Regardless of the topology, every cell is addressed by a pair of grid indices
+/// <`i`, `j`>, where `i` is the row and `j` is the column.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Topology {
+    // Square cells, each with (up to) four neighbors
+    Square,
+
+    // "Pointy-topped" hexagonal cells, each with (up to) six neighbors, where every
+    // odd row is shifted right by half a cell
+    Hex,
+
+    // Concentric rings of cells around an empty center, where each row is a ring
+    // (starting from the innermost one) and each column is a cell within the ring,
+    // counted clockwise: the outermost ring has as many cells as the map has columns,
+    // and rings are halved moving inwards so that all cells are roughly the same size
+    Polar,
+
+    // Alternating up- and down-pointing triangles, each with (up to) three neighbors:
+    // the cells to the left and right, plus the cell below (if the triangle points
+    // up) or above (if it points down)
+    Triangle,
+
+    // Square cells on the specified number of floors ("levels"), stacked on top of
+    // one another and connected by stairs: the rows of the map are split evenly
+    // between the levels, starting from the ground floor
+    Levels(usize),
+
+    // Square cells, where passages can lead off of one edge of the map and back in
+    // through the opposite edge
+    Wrapped(Wrap),
+
+    // Square cells covering the six faces of a cube, so that passages can lead off
+    // of one face and onto another: the rows of the map are split evenly between
+    // the faces, each of which is as tall as the map is wide
+    Cube,
+
+    // Octagons and squares, arranged like the squares of a checkerboard (an "upsilon"
+    // maze): octagons have (up to) eight neighbors, including the octagons that are
+    // diagonally adjacent, while squares only have the four octagons around them
+    Upsilon,
+
+    // "Flat-topped" hexagonal cells (a "sigma" maze), each with (up to) six neighbors,
+    // where every odd column is shifted down by half a cell
+    Sigma,
+}
+
+/// The position of each face of a cube, whose corners are at 0 and `2 * n` along
+/// each axis (where `n` is the number of cells along each edge). Each face is
+/// described by the corner where its first row and column meet, followed by the
+/// directions in which its columns and rows increase. The faces are stored in the
+/// order: front, right, back, left, top, bottom.
+const CUBE_FACES: [([i64; 3], [i64; 3], [i64; 3]); 6] = [
+    ([0, 1, 1], [1, 0, 0], [0, -1, 0]),
+    ([1, 1, 1], [0, 0, -1], [0, -1, 0]),
+    ([1, 1, 0], [-1, 0, 0], [0, -1, 0]),
+    ([0, 1, 0], [0, 0, 1], [0, -1, 0]),
+    ([0, 1, 0], [1, 0, 0], [0, 0, 1]),
+    ([0, 0, 1], [1, 0, 0], [0, 0, -1]),
+];
+
+/// Returns the outward-facing normal of the cube face with the specified column
+/// and row directions.
+fn get_cube_normal(u: [i64; 3], v: [i64; 3]) -> [i64; 3] {
+    [
+        v[1] * u[2] - v[2] * u[1],
+        v[2] * u[0] - v[0] * u[2],
+        v[0] * u[1] - v[1] * u[0],
+    ]
+}
+
+impl Topology {
+    /// Returns the number of cells in row `i` of a map with the specified
+    /// `dimensions`. This is the same for every row, except on polar maps.
+    pub fn get_row_width(&self, dimensions: (usize, usize), i: usize) -> usize {
+        match self {
+            Topology::Polar => {
+                // Moving inwards from the outermost ring, halve the number of cells
+                // (at most once per ring) whenever they would otherwise become less
+                // than half as wide as they are tall
+                let mut width = dimensions.1;
+                let mut halved = dimensions.0.saturating_sub(1);
+                while width % 2 == 0 && halved > i {
+                    // Rather than visiting every ring in between, jump straight to
+                    // the outermost ring where the cells are too narrow: this way,
+                    // the cost only depends on how many times the width is halved
+                    let estimate = 0.75 * width as f32 / (2.0 * std::f32::consts::PI) - 1.5;
+                    let mut ring = (estimate.max(0.0) as usize).min(halved - 1);
+                    while ring + 1 < halved && Topology::is_too_narrow(ring + 1, width) {
+                        ring += 1;
+                    }
+                    while ring > i && !Topology::is_too_narrow(ring, width) {
+                        ring -= 1;
+                    }
+
+                    if ring < i || !Topology::is_too_narrow(ring, width) {
+                        break;
+                    }
+                    width /= 2;
+                    halved = ring;
+                }
+                width
+            }
+            _ => dimensions.1,
+        }
+    }
+
+    /// Returns `true` if the cells of polar ring `ring` would be less than half as wide
+    /// as they are tall if there were `width` of them (ring `r` sits at a radius of
+    /// `r + 1.5` rows). If this holds for one ring, it holds for every ring inside it.
+    fn is_too_narrow(ring: usize, width: usize) -> bool {
+        let circumference = 2.0 * std::f32::consts::PI * (ring as f32 + 1.5);
+        circumference / (width as f32) < 0.75
+    }
+
+    /// Returns the number of rows on each level of a map with the specified
+    /// `dimensions` (which is all of them, unless the map has several levels).
+    pub fn get_rows_per_level(&self, dimensions: (usize, usize)) -> usize {
+        match self {
+            Topology::Levels(levels) => dimensions.0 / (*levels).max(1),
+            Topology::Cube => dimensions.1,
+            _ => dimensions.0,
+        }
+    }
+
+    /// Returns `true` if cell <`i`, `j`> exists on a map with the specified
+    /// `dimensions`, and `false` otherwise.
+    pub fn contains(&self, dimensions: (usize, usize), i: usize, j: usize) -> bool {
+        i < dimensions.0 && j < self.get_row_width(dimensions, i)
+    }
+
+    /// Returns the directions in which passages can lead out of cell <`i`, `j`>,
+    /// ignoring the borders of the map.
+    pub fn get_directions(&self, dimensions: (usize, usize), i: usize, j: usize) -> Vec<Direction> {
+        match self {
+            Topology::Square | Topology::Wrapped(_) | Topology::Cube => vec![
+                Direction::North,
+                Direction::South,
+                Direction::West,
+                Direction::East,
+            ],
+            Topology::Hex => vec![
+                Direction::NorthWest,
+                Direction::NorthEast,
+                Direction::West,
+                Direction::East,
+                Direction::SouthWest,
+                Direction::SouthEast,
+            ],
+            Topology::Polar => {
+                let mut directions = vec![
+                    Direction::Inward,
+                    Direction::Outward,
+                    Direction::Clockwise,
+                    Direction::CounterClockwise,
+                ];
+
+                // A cell borders two cells in the next ring if that ring is twice as wide
+                if i + 1 < dimensions.0
+                    && self.get_row_width(dimensions, i + 1) > self.get_row_width(dimensions, i)
+                {
+                    directions.push(Direction::OutwardClockwise);
+                }
+                directions
+            }
+            Topology::Triangle if Topology::is_upright(i, j) => {
+                vec![Direction::West, Direction::East, Direction::South]
+            }
+            Topology::Triangle => vec![Direction::North, Direction::West, Direction::East],
+            Topology::Upsilon if Topology::is_octagon(i, j) => vec![
+                Direction::North,
+                Direction::South,
+                Direction::West,
+                Direction::East,
+                Direction::NorthWest,
+                Direction::NorthEast,
+                Direction::SouthWest,
+                Direction::SouthEast,
+            ],
+            Topology::Upsilon => vec![
+                Direction::North,
+                Direction::South,
+                Direction::West,
+                Direction::East,
+            ],
+            Topology::Sigma => vec![
+                Direction::North,
+                Direction::NorthEast,
+                Direction::SouthEast,
+                Direction::South,
+                Direction::SouthWest,
+                Direction::NorthWest,
+            ],
+            Topology::Levels(_) => vec![
+                Direction::North,
+                Direction::South,
+                Direction::West,
+                Direction::East,
+                Direction::Up,
+                Direction::Down,
+            ],
+        }
+    }
+
+    /// Returns `true` if cell <`i`, `j`> of a triangular map points up, and `false`
+    /// if it points down. The top-left cell always points up.
+    pub fn is_upright(i: usize, j: usize) -> bool {
+        (i + j) % 2 == 0
+    }
+
+    /// Returns `true` if cell <`i`, `j`> of an upsilon map is an octagon, and `false`
+    /// if it is a square. The top-left cell is always an octagon.
+    pub fn is_octagon(i: usize, j: usize) -> bool {
+        (i + j) % 2 == 0
+    }
+
+    /// Returns the indices of the cell that is adjacent to cell <`i`, `j`> in the
+    /// specified `direction`, or `None` if that direction leads off of a map with
+    /// the specified `dimensions`.
+    pub fn get_neighbor(
+        &self,
+        dimensions: (usize, usize),
+        i: usize,
+        j: usize,
+        direction: Direction,
+    ) -> Option<(usize, usize)> {
+        match self {
+            Topology::Polar => return self.get_polar_neighbor(dimensions, i, j, direction),
+            Topology::Wrapped(wrap) => {
+                return Topology::get_wrapped_neighbor(*wrap, dimensions, i, j, direction)
+            }
+            Topology::Cube => return Topology::get_cube_neighbor(dimensions, i, j, direction),
+            _ => (),
+        }
+
+        // Stairs lead to the same cell one level up or down
+        let rows = self.get_rows_per_level(dimensions) as isize;
+
+        // Offsets are applied with signed arithmetic, then checked against the borders
+        let (di, dj): (isize, isize) = match (self, direction) {
+            (Topology::Square | Topology::Upsilon | Topology::Sigma, Direction::North) => (-1, 0),
+            (Topology::Square | Topology::Upsilon | Topology::Sigma, Direction::South) => (1, 0),
+
+            // Only octagons touch the cells diagonally adjacent to them
+            (Topology::Upsilon, Direction::NorthWest) if Topology::is_octagon(i, j) => (-1, -1),
+            (Topology::Upsilon, Direction::NorthEast) if Topology::is_octagon(i, j) => (-1, 1),
+            (Topology::Upsilon, Direction::SouthWest) if Topology::is_octagon(i, j) => (1, -1),
+            (Topology::Upsilon, Direction::SouthEast) if Topology::is_octagon(i, j) => (1, 1),
+
+            // In odd columns, the cells to either side are shifted down by one row
+            (Topology::Sigma, Direction::NorthWest) => ((j % 2) as isize - 1, -1),
+            (Topology::Sigma, Direction::NorthEast) => ((j % 2) as isize - 1, 1),
+            (Topology::Sigma, Direction::SouthWest) => ((j % 2) as isize, -1),
+            (Topology::Sigma, Direction::SouthEast) => ((j % 2) as isize, 1),
+            (Topology::Sigma, _) => return None,
+
+            // Triangles only share their horizontal side with the cell above or below
+            (Topology::Triangle, Direction::North) if !Topology::is_upright(i, j) => (-1, 0),
+            (Topology::Triangle, Direction::South) if Topology::is_upright(i, j) => (1, 0),
+
+            // Each level has its own northern and southern border
+            (Topology::Levels(_), Direction::North) if i as isize % rows > 0 => (-1, 0),
+            (Topology::Levels(_), Direction::South) if i as isize % rows < rows - 1 => (1, 0),
+            (Topology::Levels(_), Direction::Up) => (rows, 0),
+            (Topology::Levels(_), Direction::Down) => (-rows, 0),
+
+            (_, Direction::East) => (0, 1),
+            (_, Direction::West) => (0, -1),
+
+            // In odd rows, the cells above and below are shifted right by one column
+            (Topology::Hex, Direction::NorthWest) => (-1, (i % 2) as isize - 1),
+            (Topology::Hex, Direction::NorthEast) => (-1, (i % 2) as isize),
+            (Topology::Hex, Direction::SouthWest) => (1, (i % 2) as isize - 1),
+            (Topology::Hex, Direction::SouthEast) => (1, (i % 2) as isize),
+            _ => return None,
+        };
+
+        let ni = i as isize + di;
+        let nj = j as isize + dj;
+
+        if ni < 0 || nj < 0 || ni >= dimensions.0 as isize || nj >= dimensions.1 as isize {
+            return None;
+        }
+
+        Some((ni as usize, nj as usize))
+    }
+
+    /// The equivalent of `get_neighbor` for polar maps, where the number of cells
+    /// in each ring differs.
+    fn get_polar_neighbor(
+        &self,
+        dimensions: (usize, usize),
+        i: usize,
+        j: usize,
+        direction: Direction,
+    ) -> Option<(usize, usize)> {
+        let width = self.get_row_width(dimensions, i);
+
+        match direction {
+            Direction::Inward if i > 0 => {
+                let ratio = width / self.get_row_width(dimensions, i - 1);
+                Some((i - 1, j / ratio))
+            }
+            Direction::Outward | Direction::OutwardClockwise if i + 1 < dimensions.0 => {
+                let ratio = self.get_row_width(dimensions, i + 1) / width;
+                match direction {
+                    Direction::Outward => Some((i + 1, j * ratio)),
+                    _ if ratio > 1 => Some((i + 1, j * ratio + 1)),
+                    _ => None,
+                }
+            }
+            Direction::Clockwise if width > 1 => Some((i, (j + 1) % width)),
+            Direction::CounterClockwise if width > 1 => Some((i, (j + width - 1) % width)),
+            _ => None,
+        }
+    }
+
+    /// The equivalent of `get_neighbor` for square maps whose edges wrap around.
+    fn get_wrapped_neighbor(
+        wrap: Wrap,
+        dimensions: (usize, usize),
+        i: usize,
+        j: usize,
+        direction: Direction,
+    ) -> Option<(usize, usize)> {
+        // Away from the edges, this is just a square map
+        if let Some(neighbor) = Topology::Square.get_neighbor(dimensions, i, j, direction) {
+            return Some(neighbor);
+        }
+
+        let (rows, cols) = dimensions;
+        let (neighbor, opposite) = match direction {
+            Direction::North if wrap.is_vertical() => ((rows - 1, j), Direction::South),
+            Direction::South if wrap.is_vertical() => ((0, j), Direction::North),
+
+            // Crossing the left or right edge of a Möbius strip turns the map upside down
+            Direction::West | Direction::East if wrap.is_horizontal() => {
+                let i = if wrap.is_flipped() { rows - 1 - i } else { i };
+                match direction {
+                    Direction::West => ((i, cols - 1), Direction::East),
+                    _ => ((i, 0), Direction::West),
+                }
+            }
+            _ => return None,
+        };
+
+        // On very small maps, wrapping around could lead back to the same cell, or
+        // to a cell that is already adjacent on the other side: neither is allowed
+        if neighbor == (i, j)
+            || Topology::Square.get_neighbor(dimensions, i, j, opposite) == Some(neighbor)
+        {
+            return None;
+        }
+
+        Some(neighbor)
+    }
+
+    /// The equivalent of `get_neighbor` for maps that cover the faces of a cube.
+    fn get_cube_neighbor(
+        dimensions: (usize, usize),
+        i: usize,
+        j: usize,
+        direction: Direction,
+    ) -> Option<(usize, usize)> {
+        let n = dimensions.1;
+        if n == 0 || i >= 6 * n {
+            return None;
+        }
+
+        // Within a face, this is just a square map
+        let (face, row) = (i / n, i % n);
+        if let Some((row, col)) = Topology::Square.get_neighbor((n, n), row, j, direction) {
+            return Some((face * n + row, col));
+        }
+
+        // Otherwise, find the point (in units of half a cell) where the passage
+        // crosses the edge of the face, and fold it over onto the next face
+        let (origin, u, v) = CUBE_FACES[face];
+        let normal = get_cube_normal(u, v);
+        let step = match direction {
+            Direction::North => v.map(|x| -x),
+            Direction::South => v,
+            Direction::West => u.map(|x| -x),
+            Direction::East => u,
+            _ => return None,
+        };
+
+        let (n, row, col) = (n as i64, row as i64, j as i64);
+        let target: Vec<i64> = (0..3)
+            .map(|axis| {
+                let center =
+                    2 * n * origin[axis] + (2 * col + 1) * u[axis] + (2 * row + 1) * v[axis];
+                center + step[axis] - normal[axis]
+            })
+            .collect();
+
+        // The target lies on the face whose plane it touches
+        CUBE_FACES
+            .iter()
+            .enumerate()
+            .find_map(|(next, (origin, u, v))| {
+                let normal = get_cube_normal(*u, *v);
+                let axis = normal.iter().position(|x| *x != 0)?;
+                if target[axis] != 2 * n * origin[axis] {
+                    return None;
+                }
+
+                // Project the target onto the face's columns and rows
+                let offset: Vec<i64> = (0..3).map(|a| target[a] - 2 * n * origin[a]).collect();
+                let col = (0..3).map(|a| offset[a] * u[a]).sum::<i64>();
+                let row = (0..3).map(|a| offset[a] * v[a]).sum::<i64>();
+
+                if col > 0 && col < 2 * n && row > 0 && row < 2 * n {
+                    Some((next * n as usize + (row / 2) as usize, (col / 2) as usize))
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Returns the position of cell <`i`, `j`> on the surface of a cube whose edges
+    /// are `dimensions.1` units long: the corner where the cell's first row and
+    /// column meet, followed by the directions in which its columns and rows increase.
+    pub fn get_cube_frame(
+        dimensions: (usize, usize),
+        i: usize,
+        j: usize,
+    ) -> ([f32; 3], [f32; 3], [f32; 3]) {
+        let n = dimensions.1;
+        let (origin, u, v) = CUBE_FACES[i / n];
+        let (row, col) = ((i % n) as f32, j as f32);
+
+        let mut corner = [0.0; 3];
+        for axis in 0..3 {
+            corner[axis] =
+                n as f32 * origin[axis] as f32 + col * u[axis] as f32 + row * v[axis] as f32;
+        }
+
+        (corner, u.map(|x| x as f32), v.map(|x| x as f32))
+    }
+
+    /// Returns `true` if the passage leading out of cell <`i`, `j`> in the specified
+    /// `direction` wraps around to the opposite edge of the map, and `false` otherwise.
+    pub fn wraps(
+        &self,
+        dimensions: (usize, usize),
+        i: usize,
+        j: usize,
+        direction: Direction,
+    ) -> bool {
+        match self {
+            Topology::Wrapped(_) => {
+                let (rows, cols) = dimensions;
+                let edge = match direction {
+                    Direction::North => i == 0,
+                    Direction::South => i + 1 == rows,
+                    Direction::West => j == 0,
+                    Direction::East => j + 1 == cols,
+                    _ => false,
+                };
+                edge && self.get_neighbor(dimensions, i, j, direction).is_some()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the indices of all of the valid neighbors of cell <`i`, `j`> on a
+    /// map with the specified `dimensions`, respecting the borders of the map.
+    pub fn get_neighbors(
+        &self,
+        dimensions: (usize, usize),
+        i: usize,
+        j: usize,
+    ) -> Vec<(usize, usize)> {
+        let mut neighbors = vec![];
+
+        // On narrow maps, two directions can lead to the same cell (like clockwise
+        // and counterclockwise in a polar ring that is only two cells wide), but
+        // each neighbor is only listed once
+        for direction in self.get_directions(dimensions, i, j) {
+            if let Some(neighbor) = self.get_neighbor(dimensions, i, j, direction) {
+                if !neighbors.contains(&neighbor) {
+                    neighbors.push(neighbor);
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    /// Returns the direction that leads from cell `from` to the adjacent cell `to`,
+    /// or `None` if the two cells are not adjacent.
+    pub fn get_direction_between(
+        &self,
+        dimensions: (usize, usize),
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<Direction> {
+        self.get_directions(dimensions, from.0, from.1)
+            .into_iter()
+            .find(|direction| self.get_neighbor(dimensions, from.0, from.1, *direction) == Some(to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_neighbors_even_row() {
+        let actual = Topology::Hex.get_neighbors((4, 4), 2, 1);
+        let expected = vec![(1, 0), (1, 1), (2, 0), (2, 2), (3, 0), (3, 1)];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_hex_neighbors_odd_row() {
+        let actual = Topology::Hex.get_neighbors((4, 4), 1, 3);
+        let expected = vec![(0, 3), (1, 2), (2, 3)];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_get_direction_between() {
+        let dimensions = (4, 4);
+
+        assert_eq!(
+            Topology::Square.get_direction_between(dimensions, (1, 1), (0, 1)),
+            Some(Direction::North)
+        );
+        assert_eq!(
+            Topology::Hex.get_direction_between(dimensions, (1, 1), (2, 2)),
+            Some(Direction::SouthEast)
+        );
+        assert_eq!(
+            Topology::Square.get_direction_between(dimensions, (1, 1), (2, 2)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_triangle_neighbors() {
+        // Upright triangles connect downwards, the others connect upwards
+        let actual = Topology::Triangle.get_neighbors((4, 4), 1, 1);
+        let expected = vec![(1, 0), (1, 2), (2, 1)];
+        assert_eq!(actual, expected);
+
+        let actual = Topology::Triangle.get_neighbors((4, 4), 1, 2);
+        let expected = vec![(0, 2), (1, 1), (1, 3)];
+        assert_eq!(actual, expected);
+
+        // The top-left corner points up, so it has no neighbor above
+        let actual = Topology::Triangle.get_neighbors((4, 4), 0, 0);
+        let expected = vec![(0, 1), (1, 0)];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_levels_neighbors() {
+        let topology = Topology::Levels(2);
+
+        // The bottom row of the ground floor has stairs up, but no neighbor below
+        let actual = topology.get_neighbors((6, 3), 2, 1);
+        let expected = vec![(1, 1), (2, 0), (2, 2), (5, 1)];
+        assert_eq!(actual, expected);
+
+        // The top row of the next floor has stairs down, but no neighbor above
+        let actual = topology.get_neighbors((6, 3), 3, 1);
+        let expected = vec![(4, 1), (3, 0), (3, 2), (0, 1)];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_wrapped_neighbors() {
+        let dimensions = (3, 4);
+
+        // A cylinder only wraps around the left and right edges
+        let cylinder = Topology::Wrapped(Wrap::Horizontal);
+        let actual = cylinder.get_neighbors(dimensions, 0, 0);
+        let expected = vec![(1, 0), (0, 3), (0, 1)];
+        assert_eq!(actual, expected);
+
+        // A torus wraps around every edge
+        let torus = Topology::Wrapped(Wrap::Both);
+        let actual = torus.get_neighbors(dimensions, 0, 0);
+        let expected = vec![(2, 0), (1, 0), (0, 3), (0, 1)];
+        assert_eq!(actual, expected);
+        assert!(torus.wraps(dimensions, 0, 0, Direction::North));
+        assert!(!torus.wraps(dimensions, 0, 0, Direction::South));
+
+        // A Möbius strip turns upside down across the left and right edges
+        let mobius = Topology::Wrapped(Wrap::Mobius);
+        let actual = mobius.get_neighbors(dimensions, 0, 3);
+        let expected = vec![(1, 3), (0, 2), (2, 0)];
+        assert_eq!(actual, expected);
+
+        // A Klein bottle also wraps around the top and bottom edges
+        let klein = Topology::Wrapped(Wrap::Klein);
+        let actual = klein.get_neighbors(dimensions, 2, 0);
+        let expected = vec![(1, 0), (0, 0), (0, 3), (2, 1)];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_upsilon_neighbors() {
+        // Octagons border the diagonally adjacent octagons, too
+        let actual = Topology::Upsilon.get_neighbors((4, 4), 1, 1);
+        let expected = vec![
+            (0, 1),
+            (2, 1),
+            (1, 0),
+            (1, 2),
+            (0, 0),
+            (0, 2),
+            (2, 0),
+            (2, 2),
+        ];
+        assert_eq!(actual, expected);
+
+        // ...but squares only border the octagons above, below and to either side
+        let actual = Topology::Upsilon.get_neighbors((4, 4), 1, 2);
+        let expected = vec![(0, 2), (2, 2), (1, 1), (1, 3)];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_sigma_neighbors() {
+        // Even column
+        let actual = Topology::Sigma.get_neighbors((4, 4), 1, 2);
+        let expected = vec![(0, 2), (0, 3), (1, 3), (2, 2), (1, 1), (0, 1)];
+        assert_eq!(actual, expected);
+
+        // Odd column
+        let actual = Topology::Sigma.get_neighbors((4, 4), 1, 1);
+        let expected = vec![(0, 1), (1, 2), (2, 2), (2, 1), (2, 0), (1, 0)];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_cube_neighbors() {
+        let dimensions = (12, 2);
+
+        // The top-left cell of the front face borders the top and left faces
+        let actual = Topology::Cube.get_neighbors(dimensions, 0, 0);
+        let expected = vec![(9, 0), (1, 0), (6, 1), (0, 1)];
+        assert_eq!(actual, expected);
+
+        // The top-right cell of the back face borders the top and left faces, too
+        let actual = Topology::Cube.get_neighbors(dimensions, 4, 1);
+        let expected = vec![(8, 0), (5, 1), (4, 0), (6, 0)];
+        assert_eq!(actual, expected);
+
+        // Every cell has exactly four neighbors
+        for i in 0..12 {
+            for j in 0..2 {
+                assert_eq!(Topology::Cube.get_neighbors(dimensions, i, j).len(), 4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_polar_row_widths() {
+        let dimensions = (6, 32);
+        let widths: Vec<usize> = (0..6)
+            .map(|i| Topology::Polar.get_row_width(dimensions, i))
+            .collect();
+
+        assert_eq!(widths, vec![8, 16, 16, 32, 32, 32]);
+        assert!(Topology::Polar.contains(dimensions, 0, 7));
+        assert!(!Topology::Polar.contains(dimensions, 0, 8));
+
+        // Skipping ahead to the rings where the width is halved gives the same widths
+        // as visiting every ring in turn
+        for rows in 0..40 {
+            for cols in (1..200).chain((8..20).map(|power| 1 << power)) {
+                let mut expected = vec![cols; rows];
+                for ring in (0..rows.saturating_sub(1)).rev() {
+                    let width = expected[ring + 1];
+                    if width % 2 == 0 && Topology::is_too_narrow(ring, width) {
+                        expected[ring] = width / 2;
+                    } else {
+                        expected[ring] = width;
+                    }
+                }
+
+                let actual: Vec<usize> = (0..rows)
+                    .map(|i| Topology::Polar.get_row_width((rows, cols), i))
+                    .collect();
+                assert_eq!(actual, expected, "{} rows, {} columns", rows, cols);
+            }
+        }
+    }
+
+    #[test]
+    fn test_polar_neighbors() {
+        let dimensions = (6, 32);
+
+        // The first cell of a ring wraps around to the last
+        let actual = Topology::Polar.get_neighbors(dimensions, 1, 0);
+        let expected = vec![(0, 0), (2, 0), (1, 1), (1, 15)];
+        assert_eq!(actual, expected);
+
+        // The next ring is twice as wide, so there are two cells further out
+        let actual = Topology::Polar.get_neighbors(dimensions, 2, 5);
+        let expected = vec![(1, 5), (3, 10), (2, 6), (2, 4), (3, 11)];
+        assert_eq!(actual, expected);
+
+        // In a ring that is only two cells wide, both ways around lead to the same cell
+        let dimensions = (3, 2);
+        assert_eq!(Topology::Polar.get_row_width(dimensions, 0), 2);
+        assert_eq!(
+            Topology::Polar.get_neighbors(dimensions, 0, 0),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(
+            Topology::Polar.get_neighbors(dimensions, 1, 1),
+            vec![(0, 1), (2, 1), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_neighbors_are_symmetric() {
+        let dimensions = (7, 40);
+
+        for topology in [
+            Topology::Square,
+            Topology::Hex,
+            Topology::Polar,
+            Topology::Triangle,
+            Topology::Levels(7),
+            Topology::Wrapped(Wrap::Both),
+            Topology::Wrapped(Wrap::Mobius),
+            Topology::Wrapped(Wrap::Klein),
+            Topology::Cube,
+            Topology::Upsilon,
+            Topology::Sigma,
+        ]
+        .iter()
+        {
+            for i in 0..dimensions.0 {
+                for j in 0..topology.get_row_width(dimensions, i) {
+                    for (ni, nj) in topology.get_neighbors(dimensions, i, j) {
+                        assert!(topology.get_neighbors(dimensions, ni, nj).contains(&(i, j)));
+                    }
+                }
+            }
+        }
+    }
+}
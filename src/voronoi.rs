@@ -0,0 +1,373 @@
+use crate::graph::{Graph, GraphMaze};
+use crate::svg;
+use rand::{Rng, RngCore};
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+// References:
+// https://www.cs.ubc.ca/~rbridson/docs/bridson-siggraph07-poissondisk.pdf
+// https://en.wikipedia.org/wiki/Voronoi_diagram
+// https://en.wikipedia.org/wiki/Sutherland%E2%80%93Hodgman_algorithm
+
+/// A point in the plane.
+pub type Point = (f32, f32);
+
+/// The number of candidates that Poisson disk sampling tries around each point
+/// before giving up on it.
+const CANDIDATES: usize = 30;
+
+/// Returns a random set of points inside of a rectangle with the specified `size`
+/// (width, height), where no two points are closer than `radius` to each other but
+/// no gap is wide enough to fit another point. This looks far more natural than
+/// points scattered uniformly at random, which tend to clump together.
+pub fn poisson_disk(size: (f32, f32), radius: f32, rng: &mut dyn RngCore) -> Vec<Point> {
+    assert!(radius > 0.0, "The radius must be positive");
+
+    // Each grid cell is small enough to hold at most one point
+    let cell = radius / 2f32.sqrt();
+    let cols = (size.0 / cell).ceil().max(1.0) as usize;
+    let rows = (size.1 / cell).ceil().max(1.0) as usize;
+    let mut grid: Vec<Option<usize>> = vec![None; rows * cols];
+    let to_grid = |p: Point| {
+        let i = ((p.1 / cell) as usize).min(rows - 1);
+        let j = ((p.0 / cell) as usize).min(cols - 1);
+        (i, j)
+    };
+
+    let mut points = vec![];
+    let mut active = vec![];
+
+    let first = (rng.gen_range(0.0, size.0), rng.gen_range(0.0, size.1));
+    let (i, j) = to_grid(first);
+    grid[i * cols + j] = Some(0);
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let idx = rng.gen_range(0, active.len());
+        let center = points[active[idx]];
+
+        // Try points in the ring between one and two radii away from `center`
+        let found = (0..CANDIDATES).find_map(|_| {
+            let angle = rng.gen_range(0.0, 2.0 * PI);
+            let distance = rng.gen_range(radius, 2.0 * radius);
+            let candidate = (
+                center.0 + distance * angle.cos(),
+                center.1 + distance * angle.sin(),
+            );
+            if candidate.0 < 0.0
+                || candidate.0 >= size.0
+                || candidate.1 < 0.0
+                || candidate.1 >= size.1
+            {
+                return None;
+            }
+
+            // Only the grid cells within two cells of the candidate can be too close
+            let (i, j) = to_grid(candidate);
+            for ni in i.saturating_sub(2)..(i + 3).min(rows) {
+                for nj in j.saturating_sub(2)..(j + 3).min(cols) {
+                    if let Some(other) = grid[ni * cols + nj] {
+                        if distance_between(candidate, points[other]) < radius {
+                            return None;
+                        }
+                    }
+                }
+            }
+
+            Some((candidate, i * cols + j))
+        });
+
+        match found {
+            Some((point, slot)) => {
+                grid[slot] = Some(points.len());
+                active.push(points.len());
+                points.push(point);
+            }
+            None => {
+                active.swap_remove(idx);
+            }
+        }
+    }
+
+    points
+}
+
+fn distance_between(a: Point, b: Point) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// A corner of a Voronoi cell, along with the cell on the other side of the edge
+/// that runs from this corner to the next one (or `None` along the border).
+pub type Corner = (Point, Option<usize>);
+
+/// Clips `polygon` to the half of the plane that is closer to `site` than to
+/// `other`, where `other` is the site with index `neighbor`.
+fn clip(polygon: &[Corner], site: Point, other: Point, neighbor: usize) -> Vec<Corner> {
+    let middle = ((site.0 + other.0) * 0.5, (site.1 + other.1) * 0.5);
+    let normal = (other.0 - site.0, other.1 - site.1);
+
+    // Positive on `other`'s side of the bisector
+    let side = |p: Point| (p.0 - middle.0) * normal.0 + (p.1 - middle.1) * normal.1;
+
+    let mut clipped = vec![];
+    for k in 0..polygon.len() {
+        let (p, edge) = polygon[k];
+        let (q, _) = polygon[(k + 1) % polygon.len()];
+        let (dp, dq) = (side(p), side(q));
+
+        let crossing = || {
+            let t = dp / (dp - dq);
+            (p.0 + t * (q.0 - p.0), p.1 + t * (q.1 - p.1))
+        };
+
+        match (dp <= 0.0, dq <= 0.0) {
+            (true, true) => clipped.push((p, edge)),
+            // Leaving the cell: the edge from here on runs along the bisector
+            (true, false) => {
+                clipped.push((p, edge));
+                clipped.push((crossing(), Some(neighbor)));
+            }
+            // Re-entering the cell: the rest of this edge is kept
+            (false, true) => clipped.push((crossing(), edge)),
+            (false, false) => (),
+        }
+    }
+
+    clipped
+}
+
+/// A maze whose cells are the regions of a Voronoi diagram: every point of the
+/// map belongs to the cell of the closest site, and two cells are neighbors if
+/// they share an edge. Seeding the diagram with Poisson disk samples gives cells
+/// of roughly the same size but irregular shapes, which makes for organic,
+/// cave-like mazes. Any of the generators that work over a `Graph` can carve it.
+#[derive(Clone, Debug)]
+pub struct VoronoiMaze {
+    // The size (width, height) of the area covered by the cells
+    size: (f32, f32),
+
+    // The site of each cell
+    sites: Vec<Point>,
+
+    // The corners of each cell, in clockwise order (on screen)
+    polygons: Vec<Vec<Corner>>,
+
+    // Which cells border each other, and which of those are joined by passages
+    graph: GraphMaze,
+}
+
+impl VoronoiMaze {
+    /// Constructs a new maze (without any passages) that covers an area with the
+    /// specified `size` (width, height), where neighboring sites are roughly
+    /// `spacing` units apart.
+    pub fn new(size: (f32, f32), spacing: f32, rng: &mut dyn RngCore) -> VoronoiMaze {
+        VoronoiMaze::from_sites(size, poisson_disk(size, spacing, rng))
+    }
+
+    /// Constructs a new maze (without any passages) from the Voronoi diagram of
+    /// `sites`, clipped to an area with the specified `size` (width, height).
+    pub fn from_sites(size: (f32, f32), sites: Vec<Point>) -> VoronoiMaze {
+        assert!(!sites.is_empty(), "A Voronoi maze needs at least one site");
+
+        let border = vec![
+            ((0.0, 0.0), None),
+            ((size.0, 0.0), None),
+            ((size.0, size.1), None),
+            ((0.0, size.1), None),
+        ];
+
+        let mut polygons = vec![];
+        for (k, site) in sites.iter().enumerate() {
+            let mut others: Vec<usize> = (0..sites.len()).filter(|other| *other != k).collect();
+            others.sort_by(|a, b| {
+                distance_between(*site, sites[*a]).total_cmp(&distance_between(*site, sites[*b]))
+            });
+
+            let mut polygon = border.clone();
+            for other in others {
+                // Once a site is more than twice as far away as the farthest corner,
+                // its bisector (and that of any site beyond it) can't cut the cell
+                let reach = polygon
+                    .iter()
+                    .map(|(corner, _)| distance_between(*site, *corner))
+                    .fold(0.0, f32::max);
+                if distance_between(*site, sites[other]) > 2.0 * reach {
+                    break;
+                }
+
+                polygon = clip(&polygon, *site, sites[other], other);
+            }
+
+            // Edges that are (almost) zero length, where three or more cells meet at
+            // a single corner, are dropped: they don't make two cells neighbors
+            let epsilon = 1e-4 * size.0.max(size.1);
+            let mut idx = 0;
+            while idx < polygon.len() && polygon.len() > 3 {
+                let (next, _) = polygon[(idx + 1) % polygon.len()];
+                if distance_between(polygon[idx].0, next) < epsilon {
+                    polygon.remove(idx);
+                } else {
+                    idx += 1;
+                }
+            }
+
+            polygons.push(polygon);
+        }
+
+        let mut graph = GraphMaze::new(sites.len());
+        for (k, polygon) in polygons.iter().enumerate() {
+            for (_, neighbor) in polygon.iter() {
+                if let Some(neighbor) = neighbor {
+                    graph.add_edge(k, *neighbor);
+                }
+            }
+        }
+
+        VoronoiMaze {
+            size,
+            sites,
+            polygons,
+            graph,
+        }
+    }
+
+    /// Returns the size (width, height) of the area covered by the cells.
+    pub fn get_size(&self) -> (f32, f32) {
+        self.size
+    }
+
+    /// Returns the site of each cell.
+    pub fn get_sites(&self) -> &Vec<Point> {
+        &self.sites
+    }
+
+    /// Returns the corners of cell `k`, each paired with the cell on the other side
+    /// of the edge that leads to the next corner.
+    pub fn get_polygon(&self, k: usize) -> &Vec<Corner> {
+        &self.polygons[k]
+    }
+
+    /// Returns the graph of which cells border each other and which are joined.
+    pub fn get_graph(&self) -> &GraphMaze {
+        &self.graph
+    }
+
+    /// Saves an SVG drawing of the maze to `path` (see `svg::render_voronoi`).
+    pub fn save_svg(&self, path: &Path) -> std::io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(svg::render_voronoi(self).as_bytes())?;
+
+        Ok(())
+    }
+}
+
+impl Graph for VoronoiMaze {
+    type Node = usize;
+
+    fn get_nodes(&self) -> Vec<usize> {
+        self.graph.get_nodes()
+    }
+
+    fn get_node_count(&self) -> usize {
+        self.graph.get_node_count()
+    }
+
+    fn contains(&self, node: usize) -> bool {
+        self.graph.contains(node)
+    }
+
+    fn get_neighbors(&self, node: usize) -> Vec<usize> {
+        self.graph.get_neighbors(node)
+    }
+
+    fn get_open_neighbors(&self, node: usize) -> Vec<usize> {
+        self.graph.get_open_neighbors(node)
+    }
+
+    fn is_visited(&self, node: usize) -> bool {
+        self.graph.is_visited(node)
+    }
+
+    fn visit(&mut self, node: usize) {
+        self.graph.visit(node);
+    }
+
+    fn open_path_between(&mut self, to: usize, from: usize) {
+        self.graph.open_path_between(to, from);
+    }
+
+    fn get_start_node(&self, rng: &mut dyn RngCore) -> usize {
+        self.graph.get_start_node(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{Generator, Kruskal, Prims, Wilson};
+    use crate::search::breadth_first;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_poisson_disk() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let points = poisson_disk((100.0, 50.0), 5.0, &mut rng);
+
+        for (k, a) in points.iter().enumerate() {
+            assert!(a.0 >= 0.0 && a.0 < 100.0 && a.1 >= 0.0 && a.1 < 50.0);
+            for b in points[k + 1..].iter() {
+                assert!(distance_between(*a, *b) >= 5.0);
+            }
+        }
+
+        // Each point claims roughly a disk of radius 2.5 to 5, so the area is covered
+        // densely but not impossibly so
+        assert!(points.len() > 5000 / 79 && points.len() < 5000 / 19);
+    }
+
+    #[test]
+    fn test_grid_sites() {
+        // Sites on a 2x2 grid split the area into four squares: cells that only
+        // meet at the middle corner aren't neighbors
+        let sites = vec![(1.0, 1.0), (3.0, 1.0), (1.0, 3.0), (3.0, 3.0)];
+        let maze = VoronoiMaze::from_sites((4.0, 4.0), sites);
+
+        assert_eq!(
+            maze.get_graph().get_edges(),
+            vec![(0, 1), (0, 2), (1, 3), (2, 3)]
+        );
+        for k in 0..4 {
+            let polygon = maze.get_polygon(k);
+            assert_eq!(polygon.len(), 4);
+            assert_eq!(polygon.iter().filter(|(_, n)| n.is_none()).count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_generators() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let empty = VoronoiMaze::new((60.0, 40.0), 6.0, &mut rng);
+        let count = empty.get_node_count();
+
+        for generator in [
+            &Prims {} as &dyn Generator<VoronoiMaze>,
+            &Kruskal {},
+            &Wilson {},
+        ]
+        .iter()
+        {
+            let mut maze = empty.clone();
+            generator.build(&mut maze, &mut rng);
+
+            let passages: usize = (0..count).map(|k| maze.get_open_neighbors(k).len()).sum();
+            assert_eq!(passages, 2 * (count - 1));
+            for k in 1..count {
+                assert_eq!(breadth_first(&maze, k, 0).unwrap()[0], 0);
+            }
+        }
+    }
+}